@@ -4,7 +4,8 @@ extern crate test;
 extern crate safeeft;
 extern crate rand;
 
-use safeeft::{split, safesplit_branch, safesplit_straight};
+use safeeft::{split, safesplit_straight, safesplit_straight_slice,
+              safesplit_straight_f64};
 use rand::Rng;
 
 fn gen_f64(rng: &mut rand::ThreadRng) -> f64 {
@@ -26,7 +27,7 @@ fn bench_split(b: &mut test::Bencher) {
 }
 
 #[bench]
-fn bench_safesplit_branch(b: &mut test::Bencher) {
+fn bench_safesplit_straight(b: &mut test::Bencher) {
     let mut rng = rand::thread_rng();
     let mut a = [0.; 10000];
     for f in &mut a[..] {
@@ -34,19 +35,40 @@ fn bench_safesplit_branch(b: &mut test::Bencher) {
     }
 
     b.iter(|| for f in a.into_iter() {
-               test::black_box(safesplit_branch(*f));
+               test::black_box(safesplit_straight(*f));
            })
 }
 
 #[bench]
-fn bench_safesplit_straight(b: &mut test::Bencher) {
+fn bench_safesplit_straight_slice(b: &mut test::Bencher) {
     let mut rng = rand::thread_rng();
     let mut a = [0.; 10000];
     for f in &mut a[..] {
         *f = gen_f64(&mut rng);
     }
+    let mut high = [0.; 10000];
+    let mut low = [0.; 10000];
+    let mut err = [0.; 10000];
 
-    b.iter(|| for f in a.into_iter() {
-               test::black_box(safesplit_straight(*f));
+    b.iter(|| {
+               safesplit_straight_slice(&a, &mut high, &mut low, &mut err);
+               test::black_box((&high, &low, &err));
+           })
+}
+
+#[bench]
+fn bench_safesplit_straight_f64(b: &mut test::Bencher) {
+    let mut rng = rand::thread_rng();
+    let mut a = [0.; 10000];
+    for f in &mut a[..] {
+        *f = gen_f64(&mut rng);
+    }
+    let mut high = [0.; 10000];
+    let mut low = [0.; 10000];
+    let mut err = [0.; 10000];
+
+    b.iter(|| {
+               safesplit_straight_f64(&a, &mut high, &mut low, &mut err);
+               test::black_box((&high, &low, &err));
            })
 }
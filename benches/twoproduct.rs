@@ -1,11 +1,11 @@
 #![feature(test)]
-#![cfg_attr(feature = "use-fma", feature(cfg_target_feature))]
 
 extern crate test;
 extern crate safeeft;
 extern crate rand;
 
-use safeeft::{twoproduct, safetwoproduct_branch, safetwoproduct_straight};
+use safeeft::{twoproduct, safetwoproduct_branch, safetwoproduct_straight,
+              safetwoproduct_straight_slice, safetwoproduct_straight_f64};
 #[cfg(feature = "use-fma")]
 use safeeft::safetwoproduct_fma;
 use rand::Rng;
@@ -75,3 +75,39 @@ fn bench_safetwoproduct_fma(b: &mut test::Bencher) {
                test::black_box(safetwoproduct_fma(*f1, *f2));
            })
 }
+
+#[bench]
+fn bench_safetwoproduct_straight_slice(b: &mut test::Bencher) {
+    let mut rng = rand::thread_rng();
+    let mut l = [0.; 10000];
+    let mut r = [0.; 10000];
+    for i in 0..10000 {
+        l[i] = gen_f64(&mut rng);
+        r[i] = gen_f64(&mut rng);
+    }
+    let mut hi = [0.; 10000];
+    let mut lo = [0.; 10000];
+
+    b.iter(|| {
+               safetwoproduct_straight_slice(&l, &r, &mut hi, &mut lo);
+               test::black_box((&hi, &lo));
+           })
+}
+
+#[bench]
+fn bench_safetwoproduct_straight_f64(b: &mut test::Bencher) {
+    let mut rng = rand::thread_rng();
+    let mut l = [0.; 10000];
+    let mut r = [0.; 10000];
+    for i in 0..10000 {
+        l[i] = gen_f64(&mut rng);
+        r[i] = gen_f64(&mut rng);
+    }
+    let mut hi = [0.; 10000];
+    let mut lo = [0.; 10000];
+
+    b.iter(|| {
+               safetwoproduct_straight_f64(&l, &r, &mut hi, &mut lo);
+               test::black_box((&hi, &lo));
+           })
+}
@@ -1,11 +1,11 @@
 #![feature(test)]
-#![cfg_attr(feature = "use-fma", feature(cfg_target_feature))]
 
 extern crate test;
 extern crate safeeft;
 extern crate rand;
 
-use safeeft::{twosum, safetwosum_branch, safetwosum_straight};
+use safeeft::{F64x4, twosum, safetwosum_branch, safetwosum_straight,
+              safetwosum_straight_slice, safetwosum_straight_f64};
 #[cfg(feature = "use-fma")]
 use safeeft::safetwosum_fma;
 use rand::Rng;
@@ -75,3 +75,62 @@ fn bench_safetwosum_fma(b: &mut test::Bencher) {
                test::black_box(safetwosum_fma(*f1, *f2));
            })
 }
+
+#[bench]
+fn bench_safetwosum_straight_slice(b: &mut test::Bencher) {
+    let mut rng = rand::thread_rng();
+    let mut l = [0.; 10000];
+    let mut r = [0.; 10000];
+    for i in 0..10000 {
+        l[i] = gen_f64(&mut rng);
+        r[i] = gen_f64(&mut rng);
+    }
+    let mut hi = [0.; 10000];
+    let mut lo = [0.; 10000];
+
+    b.iter(|| {
+               safetwosum_straight_slice(&l, &r, &mut hi, &mut lo);
+               test::black_box((&hi, &lo));
+           })
+}
+
+#[bench]
+fn bench_safetwosum_straight_f64(b: &mut test::Bencher) {
+    let mut rng = rand::thread_rng();
+    let mut l = [0.; 10000];
+    let mut r = [0.; 10000];
+    for i in 0..10000 {
+        l[i] = gen_f64(&mut rng);
+        r[i] = gen_f64(&mut rng);
+    }
+    let mut hi = [0.; 10000];
+    let mut lo = [0.; 10000];
+
+    b.iter(|| {
+               safetwosum_straight_f64(&l, &r, &mut hi, &mut lo);
+               test::black_box((&hi, &lo));
+           })
+}
+
+#[bench]
+fn bench_safetwosum_straight_f64x4(b: &mut test::Bencher) {
+    let mut rng = rand::thread_rng();
+    let mut l = [0.; 10000];
+    let mut r = [0.; 10000];
+    for i in 0..10000 {
+        l[i] = gen_f64(&mut rng);
+        r[i] = gen_f64(&mut rng);
+    }
+    let mut hi = [0.; 10000];
+    let mut lo = [0.; 10000];
+
+    b.iter(|| {
+               for i in (0..10000).step_by(4) {
+                   let (h, e) = safetwosum_straight(F64x4::from_slice(&l[i..]),
+                                                    F64x4::from_slice(&r[i..]));
+                   h.write_to_slice(&mut hi[i..]);
+                   e.write_to_slice(&mut lo[i..]);
+               }
+               test::black_box((&hi, &lo));
+           })
+}
@@ -0,0 +1,178 @@
+use core::iter::Sum;
+use core::ops::AddAssign;
+use traits::{FloatEFT, is_finite};
+use twosum::safetwosum_straight;
+
+/// Running sum with compensation of rounding errors (the same as `sum2`).
+///
+/// Each `add` uses `safetwosum_straight`, so no precondition on the order or
+/// magnitude of summands is needed and overflow occurs only when the sum itself overflows.
+/// Once the sum is NaN or infinite, the error is zero and `value` is the same as `sum2`.
+#[derive(Clone, Copy, Debug)]
+pub struct CompensatedAccumulator<T> {
+    sum: T,
+    comp: T,
+}
+
+impl<T: FloatEFT> CompensatedAccumulator<T> {
+    #[inline]
+    pub fn new() -> CompensatedAccumulator<T> {
+        CompensatedAccumulator {
+            sum: T::zero(),
+            comp: T::zero(),
+        }
+    }
+
+    #[inline]
+    pub fn add(&mut self, x: T) {
+        let (s, e) = safetwosum_straight(self.sum.clone(), x);
+        // e is NaN if s is not finite
+        self.comp = if is_finite(s.clone()) { self.comp.clone() + e } else { T::zero() };
+        self.sum = s;
+    }
+
+    /// Adds everything accumulated in `other` to `self`.
+    #[inline]
+    pub fn merge(&mut self, other: CompensatedAccumulator<T>) {
+        self.add(other.sum);
+        if is_finite(self.sum.clone()) {
+            self.comp = self.comp.clone() + other.comp;
+        }
+    }
+
+    /// Returns the uncompensated sum and the accumulated error.
+    #[inline]
+    pub fn parts(&self) -> (T, T) {
+        (self.sum.clone(), self.comp.clone())
+    }
+
+    #[inline]
+    pub fn value(&self) -> T {
+        self.sum.clone() + self.comp.clone()
+    }
+}
+
+impl<T: FloatEFT> Default for CompensatedAccumulator<T> {
+    #[inline]
+    fn default() -> CompensatedAccumulator<T> {
+        CompensatedAccumulator::new()
+    }
+}
+
+impl<T: FloatEFT> AddAssign<T> for CompensatedAccumulator<T> {
+    #[inline]
+    fn add_assign(&mut self, x: T) {
+        self.add(x);
+    }
+}
+
+impl<T: FloatEFT> Extend<T> for CompensatedAccumulator<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.add(x);
+        }
+    }
+}
+
+impl<'a, T: FloatEFT> Extend<&'a T> for CompensatedAccumulator<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for x in iter {
+            self.add(x.clone());
+        }
+    }
+}
+
+impl<T: FloatEFT> Sum<T> for CompensatedAccumulator<T> {
+    #[inline]
+    fn sum<I: Iterator<Item = T>>(iter: I) -> CompensatedAccumulator<T> {
+        let mut acc = CompensatedAccumulator::new();
+        acc.extend(iter);
+        acc
+    }
+}
+
+impl<'a, T: FloatEFT> Sum<&'a T> for CompensatedAccumulator<T> {
+    #[inline]
+    fn sum<I: Iterator<Item = &'a T>>(iter: I) -> CompensatedAccumulator<T> {
+        let mut acc = CompensatedAccumulator::new();
+        acc.extend(iter);
+        acc
+    }
+}
+
+impl<T: FloatEFT> Sum<CompensatedAccumulator<T>> for CompensatedAccumulator<T> {
+    #[inline]
+    fn sum<I: Iterator<Item = CompensatedAccumulator<T>>>(iter: I)
+                                                          -> CompensatedAccumulator<T> {
+        let mut acc = CompensatedAccumulator::new();
+        for other in iter {
+            acc.merge(other);
+        }
+        acc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+    use sum::sum2;
+
+    #[test]
+    fn same_as_sum2() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000 {
+            let p: Vec<f64> = (0..100)
+                .map(|_| rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-100, 100)))
+                .collect();
+            let acc: CompensatedAccumulator<f64> = p.iter().sum();
+            assert_eq!(acc.value(), sum2(&p));
+
+            let mut acc = CompensatedAccumulator::new();
+            for &x in &p {
+                acc += x;
+            }
+            assert_eq!(acc.value(), sum2(&p));
+        }
+
+        let (max, inf, nan) = (f64::MAX, f64::INFINITY, f64::NAN);
+        let cases: [&[f64]; 6] = [&[1., inf], &[inf, 1.], &[max, max], &[max, max, -max],
+                                  &[-inf, 1., -inf], &[1., nan]];
+        for p in cases.iter() {
+            let acc: CompensatedAccumulator<f64> = p.iter().sum();
+            let (v, s) = (acc.value(), sum2(p));
+            assert!(v == s || (v.is_nan() && s.is_nan()), "{:?}", p);
+            assert_eq!(acc.parts().1, 0.);
+        }
+        let mut acc = CompensatedAccumulator::new();
+        acc += max;
+        acc += max;
+        assert_eq!(acc.parts(), (inf, 0.));
+    }
+
+    #[test]
+    fn merge() {
+        let p = [1e16, 1., -1e16, 3., f64::MAX, -f64::MAX];
+        let chunks: CompensatedAccumulator<f64> = p.chunks(2)
+            .map(|c| c.iter().cloned().sum::<CompensatedAccumulator<f64>>())
+            .sum();
+        assert_eq!(chunks.value(), 4.);
+
+        let mut acc = CompensatedAccumulator::default();
+        acc.extend(p.iter().cloned());
+        let mut other = CompensatedAccumulator::new();
+        other.extend(&[0.5, -0.25]);
+        acc.merge(other);
+        assert_eq!(acc.value(), 4.25);
+
+        let mut acc: CompensatedAccumulator<f64> = [f64::MAX].iter().sum();
+        acc.merge([1e16, 1., -1e16, f64::MAX].iter().sum());
+        assert_eq!(acc.parts(), (f64::INFINITY, 0.));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{error, fmt};
+use traits::{FloatEFT, SelectedFma, is_finite};
+use twosum::safetwosum_straight;
+use split::safesplit_straight;
+use twoprod::{safetwoproduct_straight, safetwoproduct_selected};
+use twodiv::safetwodiv;
+use twosqrt::safetwosqrt;
+
+/// Reason why a checked EFT has no representable result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EftError {
+    /// An input is NaN or infinite.
+    NonFinite,
+    /// The inputs are finite but out of the domain of the operation, e.g. `0 / 0` or the
+    /// square root of a negative number.
+    Domain,
+    /// The main result overflows, e.g. the sum, or the quotient of a division by zero.
+    Overflow,
+    /// The main result is finite, but the error term has bits below the smallest
+    /// subnormal number.
+    Underflow,
+}
+
+impl fmt::Display for EftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            EftError::NonFinite => "non-finite input",
+            EftError::Domain => "input out of domain",
+            EftError::Overflow => "main result overflows",
+            EftError::Underflow => "error term underflows",
+        })
+    }
+}
+
+impl error::Error for EftError {
+    fn description(&self) -> &str {
+        "non-representable result of an error-free transformation"
+    }
+}
+
+#[inline]
+fn check_inputs<T: FloatEFT>(a: &[&T]) -> Result<(), EftError> {
+    if a.iter().all(|&x| is_finite(x.clone())) {
+        Ok(())
+    } else {
+        Err(EftError::NonFinite)
+    }
+}
+
+#[inline]
+fn is_integer<T: FloatEFT>(a: T) -> bool {
+    let a = a.abs();
+    let m = T::one() / T::epsilon();
+    a >= m || (a.clone() + m.clone()) - m == a
+}
+
+/// `twosum` that returns `Err(EftError::Overflow)` instead of an infinite sum. The error
+/// of a finite sum is always representable.
+#[inline]
+pub fn checked_twosum<T: FloatEFT>(x: T, y: T) -> Result<(T, T), EftError> {
+    check_inputs(&[&x, &y])?;
+    let (s, e) = safetwosum_straight(x, y);
+    if s.is_infinite() {
+        return Err(EftError::Overflow);
+    }
+    Ok((s, e))
+}
+
+/// `twoproduct` that returns `Err` if the product overflows or its error has bits below
+/// the smallest subnormal number.
+pub fn checked_twoproduct<T: FloatEFT + SelectedFma>(a: T, b: T) -> Result<(T, T), EftError> {
+    check_inputs(&[&a, &b])?;
+    let p = a.clone() * b.clone();
+    if p.is_infinite() {
+        return Err(EftError::Overflow);
+    }
+    if p.clone().abs() >= T::radix() * T::radix() * T::min_positive() / T::epsilon() {
+        // the error is representable.
+        return Ok(safetwoproduct_selected(a, b));
+    }
+    if a == T::zero() || b == T::zero() {
+        return Ok((p, T::zero()));
+    }
+    if p == T::zero() {
+        return Err(EftError::Underflow); // 0 < |a * b| <= eta / 2
+    }
+    // scale the product by 1/eta as in `mul_up`, then the error is representable iff
+    // s + t - pc is an integer. |s| >= 1/2, so the scaled product is exact, and so are all
+    // scalings.
+    let (small, large) = if a.clone().abs() <= b.clone().abs() { (a, b) } else { (b, a) };
+    let (s, t) = safetwoproduct_straight(small / T::min_positive(), large / T::epsilon());
+    if !is_integer(s.clone()) || !is_integer(t.clone()) {
+        return Err(EftError::Underflow);
+    }
+    let pc = p.clone() / T::min_positive() / T::epsilon();
+    let d = (s - pc) + t; // exact
+    Ok((p, d * T::min_positive() * T::epsilon()))
+}
+
+/// Returns `(h, l)` with `a == h + l` where `h` and `l` fit in half the precision, as
+/// `split`. It fails with `Err(EftError::Overflow)` only if `h` rounds up to `2^(emax+1)`,
+/// e.g. for `MAX`.
+#[inline]
+pub fn checked_split<T: FloatEFT>(a: T) -> Result<(T, T), EftError> {
+    check_inputs(&[&a])?;
+    let (h, _, _) = safesplit_straight(a.clone());
+    let h = h * T::radix();
+    if h.is_infinite() {
+        return Err(EftError::Overflow);
+    }
+    Ok((h.clone(), a - h)) // exact
+}
+
+/// `safetwodiv` that returns `Err` if the quotient is not finite or the remainder
+/// `a - q * b` has bits below the smallest subnormal number.
+pub fn checked_twodiv<T: FloatEFT + SelectedFma>(a: T, b: T) -> Result<(T, T), EftError> {
+    check_inputs(&[&a, &b])?;
+    let q = a.clone() / b.clone();
+    if q.is_infinite() {
+        return Err(EftError::Overflow);
+    }
+    if !is_finite(q.clone()) {
+        return Err(EftError::Domain); // 0 / 0
+    }
+    if a.clone().abs() >= T::one() / (T::min_positive() / T::epsilon()) {
+        // the remainder is a multiple of ulp(q) * ulp(b) >= eta.
+        return Ok(safetwodiv(a, b));
+    }
+    // a - q * b is representable iff q * b - RN(q * b) is.
+    let (p, e) = checked_twoproduct(q.clone(), b)?;
+    Ok((q, (a - p) - e)) // a - p is exact
+}
+
+/// `safetwosqrt` that returns `Err` if `a` is negative or not finite, or the remainder
+/// `a - s * s` has bits below the smallest subnormal number.
+pub fn checked_twosqrt<T: FloatEFT + SelectedFma>(a: T) -> Result<(T, T), EftError> {
+    check_inputs(&[&a])?;
+    if a < T::zero() {
+        return Err(EftError::Domain);
+    }
+    if a >= T::one() / (T::min_positive() / T::epsilon()) {
+        return Ok(safetwosqrt(a));
+    }
+    let s = a.clone().sqrt();
+    let (p, e) = checked_twoproduct(s.clone(), s.clone())?;
+    Ok((s, (a - p) - e)) // a - p is exact
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+
+    use super::*;
+    use sum::sum_nearest;
+    use testutil::{gen_f32, NextFloat};
+
+    // `terms - res` if it is representable in f32, where `terms` are exact f64 numbers
+    fn expected_err(terms: &[f64], res: f32) -> Result<f32, EftError> {
+        if res.is_infinite() {
+            return Err(EftError::Overflow);
+        }
+        let mut t = terms.to_vec();
+        t.push(-res as f64);
+        let e = sum_nearest(&t) as f32;
+        t.push(-e as f64);
+        if sum_nearest(&t) == 0. { Ok(e) } else { Err(EftError::Underflow) }
+    }
+
+    #[test]
+    fn f32_all_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (a, b) = (gen_f32(&mut rng), gen_f32(&mut rng));
+            let s = a + b;
+            assert_eq!(checked_twosum(a, b),
+                       expected_err(&[a as f64, b as f64], s).map(|e| (s, e)));
+            // products of f32 numbers are exact in f64
+            let p = a * b;
+            assert_eq!(checked_twoproduct(a, b),
+                       expected_err(&[a as f64 * b as f64], p).map(|e| (p, e)),
+                       "{:e} * {:e}",
+                       a,
+                       b);
+
+            if b != 0. {
+                let q = a / b;
+                let expected = if q.is_infinite() {
+                    Err(EftError::Overflow)
+                } else {
+                    expected_err(&[a as f64, -(q as f64 * b as f64)], 0.).map(|e| (q, e))
+                };
+                assert_eq!(checked_twodiv(a, b), expected, "{:e} / {:e}", a, b);
+            }
+            let a = a.abs();
+            let s = a.sqrt();
+            assert_eq!(checked_twosqrt(a),
+                       expected_err(&[a as f64, -(s as f64 * s as f64)], 0.).map(|e| (s, e)),
+                       "sqrt({:e})",
+                       a);
+
+            match checked_split(a) {
+                Ok((h, l)) => {
+                    assert_eq!(h + l, a);
+                    // h has at most 12 bits, l at most 11 bits and a sign
+                    assert!(h.to_bits().trailing_zeros() >= 12 || h.abs() < f32::MIN_POSITIVE);
+                    assert!(l.abs() <= (h - h.next_below()) * 2f32.powi(12) ||
+                            h.abs() < f32::MIN_POSITIVE);
+                }
+                Err(e) => {
+                    assert_eq!(e, EftError::Overflow);
+                    assert!(a >= f32::MAX * (1. - 2f32.powi(-13)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extreme() {
+        let (max, eta) = (f64::MAX, 2f64.powi(-1022) * 2f64.powi(-52));
+        assert_eq!(checked_twosum(max, max), Err(EftError::Overflow));
+        assert_eq!(checked_twosum(f64::NAN, 1.), Err(EftError::NonFinite));
+        assert_eq!(checked_twosum(max, -eta), Ok((max, -eta)));
+        assert_eq!(checked_twoproduct(max, 2.), Err(EftError::Overflow));
+        assert_eq!(checked_twoproduct(f64::INFINITY, 0.), Err(EftError::NonFinite));
+        assert_eq!(checked_twoproduct(eta, 0.5), Err(EftError::Underflow));
+        assert_eq!(checked_twoproduct(f64::EPSILON, -f64::MIN_POSITIVE), Ok((-eta, 0.)));
+        assert_eq!(checked_twoproduct(3. * eta, 0.5), Err(EftError::Underflow));
+        assert_eq!(checked_twoproduct(6. * eta, 0.75), Err(EftError::Underflow));
+        assert_eq!(checked_twoproduct(6. * eta, 0.5), Ok((3. * eta, 0.)));
+        assert_eq!(checked_twoproduct(1. + f64::EPSILON, 1. + f64::EPSILON),
+                   Ok((1. + 2. * f64::EPSILON, f64::EPSILON * f64::EPSILON)));
+        assert_eq!(checked_twoproduct(eta, -0.), Ok((-0., 0.)));
+        assert_eq!(checked_split(max), Err(EftError::Overflow));
+        assert_eq!(checked_split(max.next_below()), Err(EftError::Overflow));
+        let a = (2. - 2f64.powi(-25)) * 2f64.powi(1023);
+        assert_eq!(checked_split(a), Ok((a, 0.)));
+        assert_eq!(checked_split(a.next_above()), Ok((a, a.next_above() - a)));
+        assert_eq!(checked_split(eta), Ok((0., eta)));
+        assert_eq!(checked_twodiv(1., 0.), Err(EftError::Overflow));
+        assert_eq!(checked_twodiv(0., 0.), Err(EftError::Domain));
+        assert_eq!(checked_twodiv(f64::NAN, 1.), Err(EftError::NonFinite));
+        assert_eq!(checked_twodiv(eta, 3.), Ok((0., eta)));
+        assert_eq!(checked_twodiv(max, 0.5), Err(EftError::Overflow));
+        assert_eq!(checked_twosqrt(-1.), Err(EftError::Domain));
+        assert_eq!(checked_twosqrt(-eta), Err(EftError::Domain));
+        assert_eq!(checked_twosqrt(-0.), Ok((-0., 0.)));
+        assert_eq!(checked_twosqrt(f64::NEG_INFINITY), Err(EftError::NonFinite));
+        assert_eq!(checked_twosqrt(max).map(|(s, _)| s), Ok(max.sqrt()));
+        assert_eq!(checked_twosqrt(2. * eta), Err(EftError::Underflow));
+        assert_eq!(checked_twosqrt(4. * eta), Ok((2f64.powi(-536), 0.)));
+        assert_eq!(EftError::Underflow.to_string(), "error term underflows");
+        assert_eq!(EftError::Domain.to_string(), "input out of domain");
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use traits::{FloatEFT, Fma};
+use twosum::{safetwosum_straight, safetwosum_fma};
+use twoprod::{safetwoproduct_straight, safetwoproduct_fma};
+
+const UNKNOWN: usize = 0;
+const WITHOUT_FMA: usize = 1;
+const WITH_FMA: usize = 2;
+
+static FMA_SUPPORT: AtomicUsize = AtomicUsize::new(UNKNOWN);
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn detect_fma() -> bool {
+    is_x86_feature_detected!("fma")
+}
+
+#[cfg(target_arch = "aarch64")]
+fn detect_fma() -> bool {
+    true
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn detect_fma() -> bool {
+    cfg!(target_feature = "fma")
+}
+
+/// Detects hardware `fma` on the first call and caches the result.
+#[inline]
+fn has_fma() -> bool {
+    if cfg!(target_feature = "fma") {
+        return true;
+    }
+    match FMA_SUPPORT.load(Ordering::Relaxed) {
+        UNKNOWN => {
+            let detected = detect_fma();
+            FMA_SUPPORT.store(if detected { WITH_FMA } else { WITHOUT_FMA },
+                              Ordering::Relaxed);
+            detected
+        }
+        state => state == WITH_FMA,
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "fma")]
+unsafe fn safetwosum_fma_enabled<T: FloatEFT + Fma>(x: T, y: T) -> (T, T) {
+    safetwosum_fma(x, y)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+unsafe fn safetwosum_fma_enabled<T: FloatEFT + Fma>(x: T, y: T) -> (T, T) {
+    safetwosum_fma(x, y)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "fma")]
+unsafe fn safetwoproduct_fma_enabled<T: FloatEFT + Fma>(a: T, b: T) -> (T, T) {
+    safetwoproduct_fma(a, b)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+unsafe fn safetwoproduct_fma_enabled<T: FloatEFT + Fma>(a: T, b: T) -> (T, T) {
+    safetwoproduct_fma(a, b)
+}
+
+/// `safetwosum_fma` if the running cpu supports `fma`, `safetwosum_straight` otherwise.
+///
+/// The cpu is inspected only once, so the overhead per call is a relaxed atomic load.
+#[inline]
+pub fn twosum_auto<T: FloatEFT + Fma>(x: T, y: T) -> (T, T) {
+    if has_fma() {
+        unsafe { safetwosum_fma_enabled(x, y) }
+    } else {
+        safetwosum_straight(x, y)
+    }
+}
+
+/// `safetwoproduct_fma` if the running cpu supports `fma`, `safetwoproduct_straight` otherwise.
+///
+/// The cpu is inspected only once, so the overhead per call is a relaxed atomic load.
+#[inline]
+pub fn twoproduct_auto<T: FloatEFT + Fma>(a: T, b: T) -> (T, T) {
+    if has_fma() {
+        unsafe { safetwoproduct_fma_enabled(a, b) }
+    } else {
+        safetwoproduct_straight(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn same_as_straight() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (l, r) = (rng.gen_range::<f64>(2f64.powi(-510), 2f64.powi(510)) *
+                          rng.choose(&[1., -1.]).unwrap(),
+                          rng.gen_range::<f64>(2f64.powi(-510), 2f64.powi(510)) *
+                          rng.choose(&[1., -1.]).unwrap());
+            assert_eq!(twosum_auto(l, r), safetwosum_straight(l, r));
+            assert_eq!(twoproduct_auto(l, r), safetwoproduct_straight(l, r));
+
+            let (l, r) = (l as f32, r as f32);
+            if l.is_finite() && r.is_finite() && (l * r).is_finite() && (l * r) != 0. {
+                assert_eq!(twoproduct_auto(l, r), safetwoproduct_straight(l, r));
+            }
+        }
+        let (l, r) = (f64::MAX, -f64::MAX / 5.);
+        assert_eq!(twosum_auto(l, r), safetwosum_straight(l, r));
+    }
+}
@@ -0,0 +1,150 @@
+use traits::{FloatEFT, SelectedFma, is_finite};
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_selected;
+use sum::sum_k;
+
+/// Returns `(p, e)` where `p` is the floating point dot product of `x` and `y`
+/// and `p + e` is as accurate as if it were computed in twice the working precision
+/// (Dot2 of Ogita, Rump and Oishi).
+///
+/// Uses `safetwoproduct_fma` with `use-fma` feature and `safetwoproduct_straight` otherwise.
+///
+/// If the floating point dot product overflows or an element is NaN or infinite, returns
+/// it with a zero error.
+#[inline]
+pub fn dot2_with_err<T: FloatEFT + SelectedFma>(x: &[T], y: &[T]) -> (T, T) {
+    assert_eq!(x.len(), y.len());
+    let mut p = T::zero();
+    let mut s = T::zero();
+    for (i, (a, b)) in x.iter().zip(y.iter()).enumerate() {
+        let (h, r) = safetwoproduct_selected(a.clone(), b.clone());
+        let (pp, q) = safetwosum_straight(p, h);
+        if !is_finite(pp.clone()) {
+            return (naive_dot(pp, &x[i + 1..], &y[i + 1..]), T::zero());
+        }
+        p = pp;
+        s = s + (q + r);
+    }
+    (p, s)
+}
+
+/// `acc` plus the floating point dot product of `x` and `y`, which is used once the
+/// error terms are NaN.
+#[inline]
+fn naive_dot<T: FloatEFT>(acc: T, x: &[T], y: &[T]) -> T {
+    x.iter().zip(y.iter()).fold(acc, |acc, (a, b)| acc + a.clone() * b.clone())
+}
+
+#[inline]
+pub fn dot2<T: FloatEFT + SelectedFma>(x: &[T], y: &[T]) -> T {
+    let (p, e) = dot2_with_err(x, y);
+    p + e
+}
+
+/// Returns the dot product of `x` and `y` as accurate as if it were computed in
+/// `k`-fold working precision and then rounded (DotK of Ogita, Rump and Oishi).
+///
+/// If the floating point dot product overflows or an element is NaN or infinite, returns it.
+///
+/// Panics if `k < 2`.
+pub fn dot_k<T: FloatEFT + SelectedFma>(x: &[T], y: &[T], k: usize) -> T {
+    assert_eq!(x.len(), y.len());
+    assert!(k >= 2);
+    let mut r = Vec::with_capacity(2 * x.len());
+    let mut p = T::zero();
+    for (i, (a, b)) in x.iter().zip(y.iter()).enumerate() {
+        let (h, e) = safetwoproduct_selected(a.clone(), b.clone());
+        let (pp, q) = safetwosum_straight(p, h);
+        if !is_finite(pp.clone()) {
+            return naive_dot(pp, &x[i + 1..], &y[i + 1..]);
+        }
+        p = pp;
+        r.push(e);
+        r.push(q);
+    }
+    r.push(p);
+    sum_k(&r, k - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn normal() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000 {
+            let x: Vec<f32> = (0..100)
+                .map(|_| rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-5, 5)))
+                .collect();
+            let y: Vec<f32> = (0..100)
+                .map(|_| rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-5, 5)))
+                .collect();
+            // every product and partial sum is exact in f64
+            let exact = x.iter().zip(y.iter()).fold(0f64, |acc, (&a, &b)| acc + a as f64 * b as f64);
+            let abs_dot = x.iter()
+                .zip(y.iter())
+                .fold(0f64, |acc, (&a, &b)| acc + (a as f64 * b as f64).abs());
+            let gamma = 200. * 2f64.powi(-24);
+            let res = dot2(&x, &y) as f64;
+            assert!((res - exact).abs() <= 2f64.powi(-24) * exact.abs() + gamma * gamma * abs_dot);
+        }
+    }
+
+    #[test]
+    fn ill_conditioned() {
+        assert_eq!(dot2(&[1e16, 1., -1e16], &[1., 1., 1.]), 1.);
+        assert_eq!(dot2(&[1. + f64::EPSILON, -1.], &[1. - f64::EPSILON, 1.]),
+                   -f64::EPSILON * f64::EPSILON);
+        assert_eq!(dot2::<f64>(&[], &[]), 0.);
+    }
+
+    #[test]
+    fn k_fold() {
+        let e = f64::EPSILON;
+        let (x, y) = ([1. + e, -1., 2f64.powi(-80), 2f64.powi(100), -2f64.powi(100)],
+                      [1. + e, 1., 2f64.powi(-80), 1., 1.]);
+        // exact value is 2e + e^2 + 2^-160
+        assert_eq!(dot2(&x, &y), 2. * e + e * e);
+        assert_eq!(dot_k(&x, &y, 2), dot2(&x, &y));
+        assert_eq!(dot_k(&x, &y, 3), 2. * e + (e * e + 2f64.powi(-160)));
+        assert_eq!(dot_k::<f64>(&[], &[], 3), 0.);
+    }
+
+    #[test]
+    fn extreme() {
+        let (a, b) = (3f64.powi(600) * 2f64.powi(-400), 3f64.powi(-600) * 2f64.powi(-400));
+        let p = a * b;
+        assert_eq!(dot2(&[a, 1., -1.], &[b, p, p]), p);
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        assert_eq!(dot2(&[f64::MAX, 1., -f64::MAX], &[0.5, eta, 0.5]), eta);
+    }
+
+    #[cfg(not(feature = "use-fma"))]
+    #[test]
+    fn without_fma_bound() {
+        fn norm2<T: FloatEFT>(x: &[T]) -> T {
+            dot2(x, x)
+        }
+        assert_eq!(norm2(&[3f64, 4.]), 25.);
+    }
+
+    #[test]
+    fn overflow() {
+        let (max, inf) = (f64::MAX, f64::INFINITY);
+        assert_eq!(dot2(&[max, 1.], &[2., 1.]), inf);
+        assert_eq!(dot2_with_err(&[1., max], &[1., -2.]), (-inf, 0.));
+        assert_eq!(dot2(&[max, max, 1.], &[1., 1., -max]), inf);
+        assert_eq!(dot2(&[inf, 1.], &[1., 1.]), inf);
+        assert!(dot2(&[inf, max], &[1., -2.]).is_nan());
+        assert!(dot2(&[inf, 1.], &[0., 1.]).is_nan());
+        assert_eq!(dot_k(&[max, 1.], &[2., 1.], 3), inf);
+        assert_eq!(dot_k(&[max, max, 1.], &[1., 1., -max], 3), inf);
+        assert!(dot_k(&[inf, max], &[1., -2.], 3).is_nan());
+    }
+}
@@ -0,0 +1,301 @@
+use core::ops::{Add, Sub, Mul, Div, Neg};
+use traits::{FloatEFT, is_finite};
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_straight;
+use twosqrt::safetwosqrt;
+
+/// Unevaluated sum `hi + lo` of two floating point numbers with `|lo| <= 0.5ulp(hi)`.
+///
+/// Every operation is built on `safetwosum_straight` and `safetwoproduct_straight`,
+/// so it is correct whenever the result is representable. If it overflows or an operand
+/// is NaN or infinite, `hi` is the floating point result and `lo` is zero.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct DoubleDouble<T> {
+    pub hi: T,
+    pub lo: T,
+}
+
+impl<T: FloatEFT> DoubleDouble<T> {
+    /// Creates a normalized value from arbitrary `hi` and `lo`.
+    #[inline]
+    pub fn new(hi: T, lo: T) -> DoubleDouble<T> {
+        let (hi, lo) = safetwosum_straight(hi, lo);
+        DoubleDouble { hi, lo }
+    }
+
+    #[inline]
+    pub fn from_float(a: T) -> DoubleDouble<T> {
+        DoubleDouble {
+            hi: a,
+            lo: T::zero(),
+        }
+    }
+
+    /// Nearest floating point number to `hi + lo`.
+    #[inline]
+    pub fn to_float(self) -> T {
+        self.hi + self.lo
+    }
+
+    #[inline]
+    pub fn renormalize(self) -> DoubleDouble<T> {
+        DoubleDouble::new(self.hi, self.lo)
+    }
+
+    /// Exact sum of two floating point numbers.
+    #[inline]
+    pub fn from_sum(a: T, b: T) -> DoubleDouble<T> {
+        let (hi, lo) = safetwosum_straight(a, b);
+        DoubleDouble { hi, lo }
+    }
+
+    /// Exact product of two floating point numbers.
+    #[inline]
+    pub fn from_product(a: T, b: T) -> DoubleDouble<T> {
+        let (hi, lo) = safetwoproduct_straight(a, b);
+        DoubleDouble { hi, lo }
+    }
+
+    #[inline]
+    pub fn abs(self) -> DoubleDouble<T> {
+        if self.hi < T::zero() { -self } else { self }
+    }
+
+    #[inline]
+    pub fn add_float(self, b: T) -> DoubleDouble<T> {
+        let (s, e) = safetwosum_straight(self.hi, b);
+        DoubleDouble::new(s, e + self.lo)
+    }
+
+    #[inline]
+    pub fn mul_float(self, b: T) -> DoubleDouble<T> {
+        let (p, e) = safetwoproduct_straight(self.hi, b.clone());
+        DoubleDouble::new(p, e + self.lo * b)
+    }
+
+    pub fn sqrt(self) -> DoubleDouble<T> {
+        if self.hi == T::zero() {
+            return self;
+        }
+        let (x, r) = safetwosqrt(self.hi);
+        DoubleDouble::new(x.clone(), (r + self.lo) / (T::radix() * x))
+    }
+
+    /// Multiplies both parts by a power of two `c`.
+    #[inline]
+    fn scale(self, c: T) -> DoubleDouble<T> {
+        DoubleDouble {
+            hi: self.hi * c.clone(),
+            lo: self.lo * c,
+        }
+    }
+
+    #[inline]
+    fn mul_unscaled(self, rhs: DoubleDouble<T>) -> DoubleDouble<T> {
+        let (p, e) = safetwoproduct_straight(self.hi.clone(), rhs.hi.clone());
+        DoubleDouble::new(p, e + (self.hi * rhs.lo + self.lo * rhs.hi))
+    }
+
+    #[inline]
+    fn div_unscaled(self, rhs: DoubleDouble<T>) -> DoubleDouble<T> {
+        let q1 = self.hi.clone() / rhs.hi.clone();
+        let r = self - rhs.clone().mul_float(q1.clone());
+        let q2 = r.hi.clone() / rhs.hi.clone();
+        let r = r - rhs.clone().mul_float(q2.clone());
+        let q3 = r.hi / rhs.hi;
+        DoubleDouble::new(q1, q2).add_float(q3)
+    }
+}
+
+impl<T: FloatEFT> From<T> for DoubleDouble<T> {
+    #[inline]
+    fn from(a: T) -> DoubleDouble<T> {
+        DoubleDouble::from_float(a)
+    }
+}
+
+impl<T: FloatEFT> Neg for DoubleDouble<T> {
+    type Output = DoubleDouble<T>;
+    #[inline]
+    fn neg(self) -> DoubleDouble<T> {
+        DoubleDouble {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl<T: FloatEFT> Add for DoubleDouble<T> {
+    type Output = DoubleDouble<T>;
+    #[inline]
+    fn add(self, rhs: DoubleDouble<T>) -> DoubleDouble<T> {
+        let (s1, s2) = safetwosum_straight(self.hi, rhs.hi);
+        if !is_finite(s1.clone()) {
+            return DoubleDouble::from_float(s1);
+        }
+        let (t1, t2) = safetwosum_straight(self.lo, rhs.lo);
+        let (s1, s2) = safetwosum_straight(s1, s2 + t1);
+        DoubleDouble::new(s1, s2 + t2)
+    }
+}
+
+impl<T: FloatEFT> Sub for DoubleDouble<T> {
+    type Output = DoubleDouble<T>;
+    #[inline]
+    fn sub(self, rhs: DoubleDouble<T>) -> DoubleDouble<T> {
+        self + (-rhs)
+    }
+}
+
+impl<T: FloatEFT> Mul for DoubleDouble<T> {
+    type Output = DoubleDouble<T>;
+    #[inline]
+    fn mul(self, rhs: DoubleDouble<T>) -> DoubleDouble<T> {
+        let p = self.hi.clone() * rhs.hi.clone();
+        if !is_finite(self.hi.clone()) || !is_finite(rhs.hi.clone()) {
+            return DoubleDouble::from_float(p);
+        }
+        let res = if p.is_infinite() {
+            // hi * rhs.hi may round up to overflow although the exact product does not.
+            // the larger factor is at least sqrt(MAX), so scaling it by eps is exact.
+            let (a, b) = if self.hi.clone().abs() >= rhs.hi.clone().abs() {
+                (self, rhs)
+            } else {
+                (rhs, self)
+            };
+            a.scale(T::epsilon()).mul_unscaled(b).scale(T::one() / T::epsilon())
+        } else {
+            self.mul_unscaled(rhs)
+        };
+        if is_finite(res.hi.clone()) { res } else { DoubleDouble::from_float(res.hi) }
+    }
+}
+
+impl<T: FloatEFT> Div for DoubleDouble<T> {
+    type Output = DoubleDouble<T>;
+    #[inline]
+    fn div(self, rhs: DoubleDouble<T>) -> DoubleDouble<T> {
+        let q = self.hi.clone() / rhs.hi.clone();
+        if !is_finite(q.clone()) || !is_finite(rhs.hi.clone()) {
+            return DoubleDouble::from_float(q);
+        }
+        if self.hi.clone().abs() >= T::one() / (T::min_positive() / T::epsilon()) {
+            // rhs * q1 may round up to overflow, so divide eps * self instead as `safetwodiv`.
+            // all scalings are exact.
+            return self.scale(T::epsilon()).div_unscaled(rhs).scale(T::one() / T::epsilon());
+        }
+        self.div_unscaled(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+    use twosum::safetwosum_branch;
+    use twoprod::safetwoproduct_branch;
+
+    fn to_f64(a: DoubleDouble<f32>) -> f64 {
+        a.hi as f64 + a.lo as f64
+    }
+
+    #[test]
+    fn exact_sum_and_product() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (l, r) = (rng.gen_range::<f64>(2f64.powi(-510), 2f64.powi(510)) *
+                          rng.choose(&[1., -1.]).unwrap(),
+                          rng.gen_range::<f64>(2f64.powi(-510), 2f64.powi(510)) *
+                          rng.choose(&[1., -1.]).unwrap());
+            let s = DoubleDouble::from(l) + DoubleDouble::from(r);
+            assert_eq!((s.hi, s.lo), safetwosum_branch(l, r));
+            let p = DoubleDouble::from(l) * DoubleDouble::from(r);
+            assert_eq!((p.hi, p.lo), safetwoproduct_branch(l, r));
+        }
+    }
+
+    #[test]
+    fn f32_against_f64() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (l, r) = (DoubleDouble::from_sum(rng.gen_range::<f32>(1., 2.),
+                                                 rng.gen_range::<f32>(-1., 1.) *
+                                                 2f32.powi(-30)),
+                          DoubleDouble::from_sum(rng.gen_range::<f32>(1., 2.),
+                                                 rng.gen_range::<f32>(-1., 1.) *
+                                                 2f32.powi(-30)));
+            let (lf, rf) = (to_f64(l), to_f64(r));
+            let tol = 2f64.powi(-44);
+            assert!((to_f64(l + r) - (lf + rf)).abs() <= tol * (lf + rf).abs());
+            assert!((to_f64(l - r) - (lf - rf)).abs() <= tol * 2f64.powi(-20));
+            assert!((to_f64(l * r) - lf * rf).abs() <= tol * (lf * rf).abs());
+            assert!((to_f64(l / r) - lf / rf).abs() <= tol * (lf / rf).abs());
+            assert!((to_f64(l.sqrt()) - lf.sqrt()).abs() <= tol * lf.sqrt());
+        }
+    }
+
+    #[test]
+    fn large() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (l, r) = (rng.gen_range::<f64>(2f64.powi(1020), f64::MAX),
+                          rng.gen_range::<f64>(2f64.powi(1020), f64::MAX));
+            let d = DoubleDouble::from(l) - DoubleDouble::from(r);
+            assert_eq!((d.hi, d.lo), safetwosum_branch(l, -r));
+            let q = DoubleDouble::from(l) / DoubleDouble::from(r);
+            let back = q * DoubleDouble::from(r) - DoubleDouble::from(l);
+            assert!(back.hi.abs() <= l * 2f64.powi(-100));
+            let s = DoubleDouble::from(l).sqrt();
+            let back = s * s - DoubleDouble::from(l);
+            assert!(back.hi.abs() <= l * 2f64.powi(-100));
+
+            let r = rng.gen_range::<f64>(1., 16.);
+            let q = DoubleDouble::from(l) / DoubleDouble::from(r);
+            assert_eq!(q.hi, l / r);
+            let back = q * DoubleDouble::from(r) - DoubleDouble::from(l);
+            assert!(back.hi.abs() <= l * 2f64.powi(-100));
+        }
+        let max = DoubleDouble::from(f64::MAX);
+        for &r in &[3., 1.5, 1. + f64::EPSILON, 7. / 3.] {
+            let q = max / DoubleDouble::from(r);
+            assert_eq!(q.hi, f64::MAX / r);
+            let back = q * DoubleDouble::from(r) - max;
+            assert!(back.hi.abs() <= f64::MAX * 2f64.powi(-100));
+        }
+        let s = max.sqrt();
+        let back = s * s - max;
+        assert!(back.hi.abs() <= f64::MAX * 2f64.powi(-100));
+        assert_eq!((max / DoubleDouble::from(0.5)).hi, f64::INFINITY);
+    }
+
+    #[test]
+    fn non_finite() {
+        let (max, inf) = (DoubleDouble::from(f64::MAX), DoubleDouble::from(f64::INFINITY));
+        let one = DoubleDouble::from(1.);
+        assert_eq!(inf + one, inf);
+        assert_eq!(one - inf, -inf);
+        assert_eq!(max + max, inf);
+        assert_eq!(max * DoubleDouble::from(2.), inf);
+        assert_eq!(inf * -one, -inf);
+        assert_eq!(max / DoubleDouble::from(0.5), inf);
+        assert_eq!(inf / DoubleDouble::from(3.), inf);
+        assert_eq!(one / inf, DoubleDouble::from(0.));
+        assert_eq!(one / DoubleDouble::from(0.), inf);
+        assert!((inf - inf).hi.is_nan() && (inf - inf).lo == 0.);
+        assert!((DoubleDouble::from(0.) * inf).hi.is_nan());
+    }
+
+    #[test]
+    fn comparison() {
+        let a = DoubleDouble::new(1f64, 2f64.powi(-60));
+        let b = DoubleDouble::new(1f64, -2f64.powi(-60));
+        assert!(b < a);
+        assert!(a == a);
+        assert!(-a < b);
+        assert_eq!(DoubleDouble::<f64>::from(0.).sqrt().hi, 0.);
+    }
+}
@@ -0,0 +1,143 @@
+use traits::{FloatEFT, SelectedFma};
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_selected;
+
+/// `fma` with `use-fma` feature, `fma_emulated` otherwise.
+#[cfg(feature = "use-fma")]
+#[inline]
+fn fma_selected<T: FloatEFT + SelectedFma>(a: T, b: T, c: T) -> T {
+    ::traits::fma(a, b, c)
+}
+
+/// `fma` with `use-fma` feature, `fma_emulated` otherwise.
+#[cfg(not(feature = "use-fma"))]
+#[inline]
+fn fma_selected<T: FloatEFT>(a: T, b: T, c: T) -> T {
+    ::fmaemu::fma_emulated(a, b, c)
+}
+
+/// Boldo and Muller's ErrFma, given `r == fma(a, b, c)`. Nothing overflows if
+/// `|a * b|, |c| < MAX / 8`.
+#[inline]
+fn err_fma_core<T: FloatEFT + SelectedFma>(a: T, b: T, c: T, r: T) -> (T, T) {
+    let (u1, u2) = safetwoproduct_selected(a, b);
+    let (a1, z1) = safetwosum_straight(c, u2);
+    let (b1, b2) = safetwosum_straight(u1, a1);
+    let g = (b1 - r) + b2; // exact
+    safetwosum_straight(g, z1)
+}
+
+/// Returns `(r, e1, e2)` where `r` is `a * b + c` rounded to nearest and
+/// `a * b + c == r + e1 + e2` exactly, with `e1 + e2` non-overlapping as in Boldo and
+/// Muller[1].
+///
+/// `r` is computed by `fma` with `use-fma` feature and by `fma_emulated` otherwise. As with
+/// the other EFTs, the result is correct if `e1` and `e2` are representable, also when
+/// `a * b` overflows but `r` does not.
+///
+/// 1. S. Boldo and J.-M. Muller, "Exact and Approximated Error of the FMA", IEEE Trans. Comput. 60(2), 157-164, 2011.
+pub fn err_fma<T: FloatEFT + SelectedFma>(a: T, b: T, c: T) -> (T, T, T) {
+    let r = fma_selected(a.clone(), b.clone(), c.clone());
+    let two = T::radix();
+    let huge = T::max_value() / (two.clone() * two.clone() * two.clone());
+    let tiny = two.clone() * two.clone() * two.clone() * T::min_positive() / T::epsilon();
+    let abs_p = (a.clone() * b.clone()).abs();
+    if abs_p < tiny || (abs_p < huge && c.clone().abs() < huge) {
+        let (e1, e2) = err_fma_core(a, b, c, r.clone());
+        return (r, e1, e2);
+    }
+    // halve the larger factor, which is normal since |a * b| >= tiny.
+    let (a, b) = if a.clone().abs() >= b.clone().abs() {
+        (a / two.clone(), b)
+    } else {
+        (a, b / two.clone())
+    };
+    let ch = c.clone() / two.clone();
+    if ch.clone() * two.clone() == c {
+        // r is not subnormal, so r / 2 is exact.
+        let (e1, e2) = err_fma_core(a, b, ch, r.clone() / two.clone());
+        return (r, e1 * two.clone(), e2 * two);
+    }
+    // |c| < 2^(emin+1) and |a * b| >= MAX / 8, so r == RN(a * b) unless c breaks a tie, and
+    // the exact a * b - r is representable.
+    let (p, q) = safetwoproduct_selected(a, b);
+    let d = (p * two.clone() - r.clone()) + q * two; // exact
+    let (e1, e2) = safetwosum_straight(d, c);
+    (r, e1, e2)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use sum::sum_nearest;
+    use testutil::{gen_f32, NextFloat};
+
+    fn check_f32(a: f32, b: f32, c: f32) {
+        let (r, e1, e2) = err_fma(a, b, c);
+        assert_eq!(r, a.mul_add(b, c));
+        // the exact sum of f64 numbers with exponents >= -298 is zero iff its rounding is.
+        let ab = a as f64 * b as f64;
+        assert_eq!(sum_nearest(&[ab, c as f64, -r as f64, -e1 as f64, -e2 as f64]), 0.,
+                   "err_fma({:e}, {:e}, {:e}) = ({:e}, {:e}, {:e})", a, b, c, r, e1, e2);
+        assert_eq!(e1 + e2, e1);
+    }
+
+    #[test]
+    fn f32_all_range() {
+        let mut rng = rand::thread_rng();
+        let mut n = 0;
+        while n < 1000000 {
+            let (a, b, c) = (gen_f32(&mut rng), gen_f32(&mut rng), gen_f32(&mut rng));
+            let ab = a as f64 * b as f64;
+            // otherwise the error may be non-representable
+            if ab.abs() < 2f64.powi(-100) || a.mul_add(b, c).is_infinite() {
+                continue;
+            }
+            check_f32(a, b, c);
+            let p = a * b;
+            if p.is_finite() {
+                check_f32(a, b, -p);
+                check_f32(a, b, -p.next_above());
+            }
+            n += 1;
+        }
+    }
+
+    #[test]
+    fn f64_normal() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let a = rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-400, 400));
+            let b = rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-400, 400));
+            let c = rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-400, 400));
+            let (r, e1, e2) = err_fma(a, b, c);
+            assert_eq!(r, a.mul_add(b, c));
+            // a * b + c - r is exact with fma and so is its rounding.
+            let (p, q) = (a * b, a.mul_add(b, -a * b));
+            assert_eq!(sum_nearest(&[p, q, c, -r, -e1, -e2]), 0.);
+            assert_eq!(e1 + e2, e1);
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let max = f64::MAX;
+        assert_eq!(err_fma(max, 1.5, -max), (max / 2., 0., 0.));
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        let ulp = max - max.next_below();
+        assert_eq!(err_fma(ulp / 2., 1., max.next_below()), (max.next_below(), ulp / 2., 0.));
+        assert_eq!(err_fma(max, 1., eta), (max, eta, 0.));
+        assert_eq!(err_fma(max / 4., 4., -eta), (max, -eta, 0.));
+        let eta = 2f32.powi(-126) * 2f32.powi(-23);
+        check_f32(f32::MAX, 2., -f32::MAX);
+        check_f32(f32::MAX / 2., 1. + f32::EPSILON, eta);
+        // a * b is a tie, which c breaks
+        check_f32(3. * 2f32.powi(125), 1. + f32::EPSILON, eta);
+        check_f32(3. * 2f32.powi(125), 1. + f32::EPSILON, -eta);
+    }
+}
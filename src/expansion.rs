@@ -0,0 +1,342 @@
+use core::cmp::Ordering;
+use core::ops::{Add, Sub, Mul, Neg};
+use traits::{FloatEFT, SelectedFma};
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_selected;
+use sum::{grow_partials, round_partials};
+
+/// Exact sum of floating point numbers, kept as non-overlapping components in increasing
+/// order of magnitude without zeros, as in Shewchuk[1].
+///
+/// Every operation is built on `safetwosum_straight` and `safetwoproduct_straight`
+/// (`safetwoproduct_fma` with `use-fma` feature), so it is exact whenever every component
+/// of the result is representable, also near overflow and in the subnormal range.
+///
+/// 1. J. R. Shewchuk, "Adaptive Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates", Discrete Comput. Geom. 18(3), 305-363, 1997.
+#[derive(Clone, Debug)]
+pub struct Expansion<T> {
+    components: Vec<T>,
+}
+
+impl<T: FloatEFT> Expansion<T> {
+    /// Zero, which has no components.
+    #[inline]
+    pub fn new() -> Expansion<T> {
+        Expansion { components: Vec::new() }
+    }
+
+    #[inline]
+    pub fn from_float(a: T) -> Expansion<T> {
+        let mut res = Expansion::new();
+        res.grow(a);
+        res
+    }
+
+    /// Exact sum of two floating point numbers.
+    #[inline]
+    pub fn from_sum(a: T, b: T) -> Expansion<T> {
+        let mut res = Expansion::from_float(a);
+        res.grow(b);
+        res
+    }
+
+    /// Exact product of two floating point numbers.
+    #[inline]
+    pub fn from_product(a: T, b: T) -> Expansion<T>
+        where T: SelectedFma
+    {
+        let (p, e) = safetwoproduct_selected(a, b);
+        Expansion::from_sum(e, p)
+    }
+
+    /// Components in increasing order of magnitude.
+    #[inline]
+    pub fn components(&self) -> &[T] {
+        &self.components
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Sign of the exact value, which is that of the largest component.
+    #[inline]
+    pub fn sign(&self) -> Ordering {
+        match self.components.last() {
+            Some(a) => a.partial_cmp(&T::zero()).unwrap_or(Ordering::Equal),
+            None => Ordering::Equal,
+        }
+    }
+
+    /// Adds `x` exactly (Grow-Expansion with zero elimination).
+    #[inline]
+    pub fn grow(&mut self, x: T) {
+        grow_partials(&mut self.components, x);
+    }
+
+    /// Exact product with a floating point number (Scale-Expansion with zero elimination).
+    pub fn scale(&self, b: T) -> Expansion<T>
+        where T: SelectedFma
+    {
+        let mut h = Vec::with_capacity(2 * self.components.len());
+        let mut iter = self.components.iter();
+        let mut q = match iter.next() {
+            Some(e) => {
+                let (q, h0) = safetwoproduct_selected(e.clone(), b.clone());
+                push_nonzero(&mut h, h0);
+                q
+            }
+            None => return Expansion::new(),
+        };
+        for e in iter {
+            let (p, t) = safetwoproduct_selected(e.clone(), b.clone());
+            let (s, h1) = safetwosum_straight(q, t);
+            push_nonzero(&mut h, h1);
+            let (s, h2) = safetwosum_straight(p, s);
+            push_nonzero(&mut h, h2);
+            q = s;
+        }
+        push_nonzero(&mut h, q);
+        Expansion { components: h }
+    }
+
+    /// Rewrites the components so that the largest one approximates the value within an
+    /// ulp, which usually reduces their number (Compress).
+    pub fn compress(&mut self) {
+        let m = self.components.len();
+        if m == 0 {
+            return;
+        }
+        let e = &self.components;
+        let mut g = e.clone();
+        let mut bottom = m - 1;
+        let mut q = e[m - 1].clone();
+        for x in e[..m - 1].iter().rev() {
+            let (big, small) = safetwosum_straight(q, x.clone());
+            if small != T::zero() {
+                g[bottom] = big;
+                bottom -= 1;
+                q = small;
+            } else {
+                q = big;
+            }
+        }
+        g[bottom] = q.clone();
+        let mut h = Vec::with_capacity(m - bottom);
+        for x in &g[bottom + 1..] {
+            let (big, small) = safetwosum_straight(x.clone(), q);
+            push_nonzero(&mut h, small);
+            q = big;
+        }
+        h.push(q);
+        self.components = h;
+    }
+
+    /// Sum of the components from the smallest, a cheap approximation of the value
+    /// (Estimate).
+    #[inline]
+    pub fn estimate(&self) -> T {
+        self.components.iter().fold(T::zero(), |acc, x| acc + x.clone())
+    }
+
+    /// The value rounded to nearest.
+    #[inline]
+    pub fn to_float(&self) -> T {
+        round_partials(&self.components)
+    }
+}
+
+#[inline]
+fn push_nonzero<T: FloatEFT>(h: &mut Vec<T>, x: T) {
+    if x != T::zero() {
+        h.push(x);
+    }
+}
+
+impl<T: FloatEFT> Default for Expansion<T> {
+    #[inline]
+    fn default() -> Expansion<T> {
+        Expansion::new()
+    }
+}
+
+impl<T: FloatEFT> From<T> for Expansion<T> {
+    #[inline]
+    fn from(a: T) -> Expansion<T> {
+        Expansion::from_float(a)
+    }
+}
+
+impl<T: FloatEFT> Neg for Expansion<T> {
+    type Output = Expansion<T>;
+    #[inline]
+    fn neg(self) -> Expansion<T> {
+        Expansion { components: self.components.into_iter().map(|x| -x).collect() }
+    }
+}
+
+impl<T: FloatEFT> Add for Expansion<T> {
+    type Output = Expansion<T>;
+    /// Grows the longer operand by every component of the other (Expansion-Sum).
+    fn add(self, rhs: Expansion<T>) -> Expansion<T> {
+        let (mut res, other) = if self.components.len() >= rhs.components.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        for x in other.components {
+            res.grow(x);
+        }
+        res
+    }
+}
+
+impl<T: FloatEFT> Sub for Expansion<T> {
+    type Output = Expansion<T>;
+    #[inline]
+    fn sub(self, rhs: Expansion<T>) -> Expansion<T> {
+        self + (-rhs)
+    }
+}
+
+impl<T: FloatEFT + SelectedFma> Mul<T> for Expansion<T> {
+    type Output = Expansion<T>;
+    #[inline]
+    fn mul(self, rhs: T) -> Expansion<T> {
+        self.scale(rhs)
+    }
+}
+
+impl<T: FloatEFT + SelectedFma> Mul for Expansion<T> {
+    type Output = Expansion<T>;
+    /// Sum of `self` scaled by every component of `rhs`.
+    fn mul(self, rhs: Expansion<T>) -> Expansion<T> {
+        let (a, b) = if self.components.len() >= rhs.components.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        let mut res = Expansion::new();
+        for x in b.components {
+            for y in a.scale(x).components {
+                res.grow(y);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use sum::sum_nearest;
+    use testutil::{lowest_bit, NextFloat};
+
+    fn gen_exact_f32(rng: &mut rand::ThreadRng) -> f32 {
+        // multiples of 2^-34, so that every product is representable
+        rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-10, 30))
+    }
+
+    // `exact` is a list of f64 numbers whose exact sum is the expected value.
+    fn check(e: &Expansion<f32>, exact: &[f64]) {
+        let mut diff = exact.to_vec();
+        diff.extend(e.components().iter().map(|&a| -a as f64));
+        assert_eq!(sum_nearest(&diff), 0., "{:?}", e);
+        for w in e.components().windows(2) {
+            assert!(w[0] != 0. && (w[0].abs() as f64) < lowest_bit(w[1]), "{:?}", e);
+        }
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..30000 {
+            let x: Vec<f32> = (0..10).map(|_| gen_exact_f32(&mut rng)).collect();
+            let mut a = Expansion::new();
+            for &v in &x[..5] {
+                a.grow(v);
+            }
+            let exact_a: Vec<f64> = x[..5].iter().map(|&v| v as f64).collect();
+            check(&a, &exact_a);
+            assert_eq!(a.to_float(), sum_nearest(&x[..5]));
+
+            let b = Expansion::from_sum(x[5], x[6]) + Expansion::from_product(x[7], x[8]);
+            let exact_b = [x[5] as f64, x[6] as f64, x[7] as f64 * x[8] as f64];
+            check(&b, &exact_b);
+
+            let s = a.clone() - b.clone();
+            let mut exact_s = exact_a.clone();
+            exact_s.extend(exact_b.iter().map(|&v| -v));
+            check(&s, &exact_s);
+            assert_eq!(s.sign(), sum_nearest(&exact_s).partial_cmp(&0.).unwrap());
+
+            // products of f32 are exact in f64
+            let p = a.clone().scale(x[9]);
+            let exact_p: Vec<f64> = exact_a.iter().map(|&v| v * x[9] as f64).collect();
+            check(&p, &exact_p);
+
+            let mut c = a.clone() * b.clone();
+            let mut exact_c = Vec::new();
+            for &u in &exact_a {
+                for &v in &exact_b[..2] {
+                    exact_c.push(u * v);
+                }
+            }
+            // (x[7] * x[8]) * x[i] may need more than 53 bits
+            let b2 = Expansion::from_product(x[7], x[8]);
+            for &u in &x[..5] {
+                for &v in b2.components() {
+                    exact_c.push(u as f64 * v as f64);
+                }
+            }
+            check(&c, &exact_c);
+            let n = c.components().len();
+            c.compress();
+            check(&c, &exact_c);
+            assert!(c.components().len() <= n);
+            let val = sum_nearest(&exact_c);
+            if let Some(&top) = c.components().last() {
+                assert!((val - top as f64).abs() < (top.abs().next_above() - top.abs()) as f64);
+            }
+            let est = c.estimate() as f64;
+            assert!((est - val).abs() <= val.abs() * 2f64.powi(-22));
+        }
+    }
+
+    #[test]
+    fn extreme() {
+        let (max, eta) = (f64::MAX, 2f64.powi(-1022) * 2f64.powi(-52));
+        let ulp = max - max.next_below();
+        let mut a = Expansion::from(max);
+        a.grow(eta);
+        a.grow(ulp / 4.);
+        assert_eq!(a.components(), &[eta, ulp / 4., max]);
+        assert_eq!(a.to_float(), max);
+        a.grow(-max);
+        assert_eq!(a.components(), &[eta, ulp / 4.]);
+        assert_eq!(a.sign(), Ordering::Greater);
+        assert_eq!((-a.clone()).sign(), Ordering::Less);
+
+        let b = Expansion::from_sum(max / 2., 3. * eta).scale(2.);
+        assert_eq!(b.components(), &[6. * eta, max]);
+        let b = Expansion::from_sum(max.next_below() / 4., 1.) * 4.;
+        assert_eq!(b.components(), &[4., max.next_below()]);
+        let c = Expansion::from_product(2f64.powi(-600), 3. * 2f64.powi(-470));
+        assert_eq!(c.components(), &[3. * 2f64.powi(-1022) * 2f64.powi(-48)]);
+        let d = Expansion::from(1. + f64::EPSILON) * Expansion::from(1. - f64::EPSILON);
+        assert_eq!(d.components(), &[-f64::EPSILON * f64::EPSILON, 1.]);
+
+        let z = Expansion::from_sum(1., -1.);
+        assert!(z.is_zero() && z.sign() == Ordering::Equal && z.to_float() == 0.);
+        assert!((z.clone() * 3.).is_zero() && (z.clone() * a.clone()).is_zero());
+        let mut z = z;
+        z.compress();
+        assert!(z.is_zero() && z.estimate() == 0.);
+    }
+}
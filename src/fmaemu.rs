@@ -0,0 +1,230 @@
+use traits::{FloatEFT, is_finite};
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_straight;
+use rounding::{succ, pred};
+use sum::sum_nearest;
+
+/// `a` must satisfy `|a| >= 2^(emin+1)`, so that `ulp(a) / 2` is representable.
+#[inline]
+fn is_odd<T: FloatEFT>(a: T) -> bool {
+    let a = a.abs();
+    if a == T::max_value() {
+        return true;
+    }
+    let half_ulp = (succ(a.clone()) - a.clone()) / T::radix(); // all operations are exact
+    // ties to even: a + half_ulp rounds away from a iff the last bit of a is odd
+    a.clone() + half_ulp != a
+}
+
+/// Returns `a + b` rounded to odd: the sum itself if it is representable, otherwise
+/// the neighbour of the exact sum whose last bit is odd.
+#[inline]
+fn add_round_to_odd<T: FloatEFT>(a: T, b: T) -> T {
+    let (s, e) = safetwosum_straight(a, b);
+    if e == T::zero() {
+        return s;
+    }
+    // a + b is inexact, so |s| >= 2^(emin+1)
+    let n = if e > T::zero() { succ(s.clone()) } else { pred(s.clone()) };
+    if is_odd(s.clone()) { s } else { n }
+}
+
+/// Algorithm 5.4 of Boldo and Melquiond: rounds `uh + ul + c` to nearest, where
+/// `uh + ul` is the exact product. `vh` overflows only if the result overflows.
+#[inline]
+fn fma_core<T: FloatEFT>(uh: T, ul: T, c: T) -> T {
+    let (th, tl) = safetwosum_straight(c, ul);
+    let (vh, vl) = safetwosum_straight(uh, th);
+    if vh.is_infinite() {
+        return vh;
+    }
+    vh + add_round_to_odd(tl, vl)
+}
+
+/// `fma_emulated` for `|a * b| < 2^(emin+p+2)` and `|c| < 2^(emin+2p+5)`, where the error
+/// of the product may underflow.
+fn fma_small<T: FloatEFT>(a: T, b: T, c: T) -> T {
+    let (x, y) = if a.clone().abs() <= b.clone().abs() {
+        (a.clone(), b.clone())
+    } else {
+        (b.clone(), a.clone())
+    };
+    if x == T::zero() {
+        return a * b + c;
+    }
+    // multiply everything by 1/eta, so the result has to be rounded to integer if it is
+    // subnormal. all scalings are exact.
+    let (x, y) = (x / T::min_positive(), y / T::epsilon());
+    let cs = c.clone() / T::min_positive() / T::epsilon();
+    let (ph, pl) = safetwoproduct_straight(x, y);
+    let half = T::one() / T::radix();
+    if ph.clone().abs() < half {
+        // |a * b| < eta / 2 and c is a multiple of eta
+        return if c == T::zero() { a * b } else { c };
+    }
+    // |x * y| >= 1/4, so nothing underflows in safetwoproduct_straight
+    let r = fma_core(ph.clone(), pl.clone(), cs.clone());
+    if r == T::zero() {
+        // exact cancellation, so a * b is exact and the sign of zero follows IEEE 754.
+        return a * b + c;
+    }
+    let m = T::one() / T::epsilon(); // 2^(p-1), the smallest normal number divided by eta
+    if r.clone().abs() >= m {
+        return r * T::min_positive() * T::epsilon();
+    }
+    let m = if r > T::zero() { m } else { -m };
+    let mut n = (r.clone() + m.clone()) - m; // r rounded to integer
+    if (r.clone() - n.clone()).abs() == half {
+        // r is a tie, but the exact result may not be. the components are multiples of
+        // eta, so the rounded sum has the sign of the exact one.
+        let d = sum_nearest(&[ph, pl, cs, -r.clone()]);
+        if d > T::zero() {
+            n = r.clone() + half;
+        } else if d < T::zero() {
+            n = r.clone() - half;
+        }
+    }
+    if n == T::zero() {
+        return r * T::zero();
+    }
+    n * T::min_positive() * T::epsilon()
+}
+
+/// Returns `a * b + c` correctly rounded to nearest (ties to even), using only the
+/// basic arithmetic operations.
+///
+/// The exact product from `safetwoproduct_straight` is added to `c` with the algorithm of
+/// Boldo and Melquiond[1], which rounds the intermediate sum to odd to avoid double
+/// rounding. The operands are scaled when the product is close to overflow or its error
+/// may underflow, so the result is correct over the whole range, e.g. if `a * b`
+/// overflows but `a * b + c` does not, or if the result is subnormal.
+///
+/// 1. S. Boldo and G. Melquiond, "Emulation of FMA and Correctly Rounded Sums: Proved Algorithms Using Rounding to Odd", IEEE Trans. Comput. 57(4), 462-471, 2008.
+pub fn fma_emulated<T: FloatEFT>(a: T, b: T, c: T) -> T {
+    if !is_finite(a.clone()) || !is_finite(b.clone()) {
+        return a * b + c;
+    }
+    if !is_finite(c.clone()) {
+        return c; // a * b may overflow, but the exact product is finite
+    }
+    let two = T::radix();
+    let (uh, ul) = safetwoproduct_straight(a.clone(), b.clone());
+    let (abs_uh, abs_c) = (uh.clone().abs(), c.clone().abs());
+    let tiny = two.clone() * two.clone() * two.clone() * T::min_positive() / T::epsilon();
+    if abs_uh < tiny {
+        if abs_c >= tiny * two.clone() * two.clone() * two.clone() * two / T::epsilon() {
+            // |a * b| < ulp(c) / 4
+            return c;
+        }
+        return fma_small(a, b, c);
+    }
+    let huge = T::max_value() / (two.clone() * two.clone() * two.clone());
+    if abs_uh >= huge || abs_c >= huge {
+        // halve the larger factor and c. |a * b| >= 2^(emin+p+2), so the larger factor is
+        // normal, and if c / 2 is inexact, c only acts as a sticky bit.
+        let (a, b) = if a.clone().abs() >= b.clone().abs() {
+            (a / two.clone(), b)
+        } else {
+            (a, b / two.clone())
+        };
+        let ch = c.clone() / two.clone();
+        let ch = if ch == T::zero() { c } else { ch };
+        let (uh, ul) = safetwoproduct_straight(a, b);
+        if uh.is_infinite() {
+            // |a * b| >= 2 * (MAX + ulp(MAX) / 2), so a * b + c overflows as well
+            return uh;
+        }
+        return fma_core(uh, ul, ch) * two;
+    }
+    fma_core(uh, ul, c)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use testutil::{gen_f32, NextFloat};
+
+    fn check_f32(a: f32, b: f32, c: f32) {
+        let (res, expected) = (fma_emulated(a, b, c), a.mul_add(b, c));
+        assert!(res.to_bits() == expected.to_bits() || (res.is_nan() && expected.is_nan()),
+                "fma({:e}, {:e}, {:e}) = {:e}, expected {:e}", a, b, c, res, expected);
+    }
+
+    #[test]
+    fn f32_all_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (a, b, c) = (gen_f32(&mut rng), gen_f32(&mut rng), gen_f32(&mut rng));
+            check_f32(a, b, c);
+            // let a * b and c cancel
+            let p = a * b;
+            check_f32(a, b, -p);
+            check_f32(a, b, -p + (p - p.next_above()) / 4.);
+            check_f32(a, b, -p.next_above());
+        }
+    }
+
+    #[test]
+    fn f32_small() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let a = rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-149, 128));
+            let b = rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-149, 128));
+            let c = rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-149, -90));
+            check_f32(a, b, c);
+            check_f32(a, b, -(a * b) + c * f32::EPSILON);
+        }
+        // ties at the subnormal grid
+        let eta = 2f32.powi(-126) * 2f32.powi(-23);
+        check_f32(eta / f32::EPSILON, f32::EPSILON / 2., eta);
+        check_f32(eta / f32::EPSILON, f32::EPSILON / 2., 2. * eta);
+        check_f32(eta / f32::EPSILON, f32::EPSILON / 2. * (1. + f32::EPSILON), 2. * eta);
+        check_f32(eta / f32::EPSILON, -f32::EPSILON / 2. * (1. + f32::EPSILON), 2. * eta);
+        check_f32(eta, 0.5, -0.);
+        check_f32(-eta, 0.5, 0.);
+        check_f32(-eta, 0.25, 0.);
+        check_f32(eta, -eta, 0.);
+        check_f32(eta, eta, -0.);
+    }
+
+    #[test]
+    fn f64_all_range() {
+        let mut rng = rand::thread_rng();
+        let gen = |rng: &mut rand::ThreadRng| {
+            rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-1074, 1024))
+        };
+        for _ in 0..1000000 {
+            let (a, b, c) = (gen(&mut rng), gen(&mut rng), gen(&mut rng));
+            assert_eq!(fma_emulated(a, b, c).to_bits(), a.mul_add(b, c).to_bits());
+            let p = a * b;
+            let c = -p + (p.next_above() - p) * rng.gen_range::<f64>(-1., 1.);
+            assert_eq!(fma_emulated(a, b, c).to_bits(), a.mul_add(b, c).to_bits());
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let max = f64::MAX;
+        // a * b overflows, but a * b + c does not
+        assert_eq!(fma_emulated(max, 1.5, -max), max / 2.);
+        assert_eq!(fma_emulated(max, 2., -max), max);
+        assert_eq!(fma_emulated(max, -2., max), -max);
+        assert_eq!(fma_emulated(max, 1. + f64::EPSILON, -max), max * f64::EPSILON);
+        assert_eq!(fma_emulated(max, 2., -max / 2.), f64::INFINITY);
+        assert_eq!(fma_emulated(max, max, -max), f64::INFINITY);
+        // the exact result is just below the overflow threshold
+        let ulp = max - max.next_below();
+        assert_eq!(fma_emulated(max, 1., ulp / 2.), f64::INFINITY);
+        assert_eq!(fma_emulated(ulp / 2., 1. - f64::EPSILON / 2., max), max);
+        assert_eq!(fma_emulated(max / 4., 4., -f64::from_bits(1)), max);
+        assert!(fma_emulated(f64::INFINITY, 0., 1.).is_nan());
+        assert_eq!(fma_emulated(1., 1., f64::NEG_INFINITY), f64::NEG_INFINITY);
+        assert_eq!(fma_emulated(f64::INFINITY, -1., 1.), f64::NEG_INFINITY);
+        assert!(fma_emulated(1., 1., f64::NAN).is_nan());
+    }
+}
@@ -0,0 +1,104 @@
+use traits::{FloatEFT, SelectedFma};
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_selected;
+
+/// Returns `(h, c, e)` where `h` is the Horner evaluation of `coeffs` at `x`, `c` is the
+/// Horner evaluation of the error polynomial and `e` is that of its absolute values at `|x|`.
+#[inline]
+fn comp_horner_impl<T: FloatEFT + SelectedFma>(coeffs: &[T], x: T) -> (T, T, T) {
+    let mut iter = coeffs.iter().rev();
+    let mut s = match iter.next() {
+        Some(a) => a.clone(),
+        None => return (T::zero(), T::zero(), T::zero()),
+    };
+    let mut c = T::zero();
+    let mut e = T::zero();
+    let abs_x = x.clone().abs();
+    for a in iter {
+        let (p, pi) = safetwoproduct_selected(s, x.clone());
+        let (ss, sigma) = safetwosum_straight(p, a.clone());
+        s = ss;
+        c = c * x.clone() + (pi.clone() + sigma.clone());
+        e = e * abs_x.clone() + (pi.abs() + sigma.abs());
+    }
+    (s, c, e)
+}
+
+#[inline]
+fn comp_horner_bound<T: FloatEFT>(n: usize, res: T, e: T) -> T {
+    let u = T::epsilon() / T::radix();
+    let mut n_float = T::zero();
+    for _ in 0..n {
+        n_float = n_float + T::one();
+    }
+    let two = T::radix();
+    let k = two.clone() * n_float.clone() - T::one();
+    let gamma = (k.clone() * u.clone()) / (T::one() - k * u.clone());
+    let alpha = (gamma * e) /
+                (T::one() - two.clone() * (n_float + T::one()) * u.clone());
+    (u.clone() * res.abs() + alpha) / (T::one() - two * u)
+}
+
+/// Evaluates the polynomial `coeffs[0] + coeffs[1] * x + ... + coeffs[n] * x^n` with the
+/// compensated Horner scheme of Graillat, Langlois and Louvet[1]. The result is as accurate
+/// as if it were computed in twice the working precision.
+///
+/// 1. S. Graillat, Ph. Langlois and N. Louvet, "Algorithms for Accurate, Validated and Fast Polynomial Evaluation", Japan J. Indust. Appl. Math. 26, 191-214, 2009.
+pub fn comp_horner<T: FloatEFT + SelectedFma>(coeffs: &[T], x: T) -> T {
+    let (s, c, _) = comp_horner_impl(coeffs, x);
+    s + c
+}
+
+/// Returns `(res, bound)` where `res == comp_horner(coeffs, x)` and `bound` is an
+/// a posteriori bound of `|res - p(x)|`, valid unless underflow occurs.
+pub fn comp_horner_with_bound<T: FloatEFT + SelectedFma>(coeffs: &[T], x: T) -> (T, T) {
+    let (s, c, e) = comp_horner_impl(coeffs, x);
+    let res = s + c;
+    (res.clone(), comp_horner_bound(coeffs.len().saturating_sub(1), res, e))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use self::rand::Rng;
+
+    use super::*;
+
+    // (x - 2)^5
+    const P5: [f64; 6] = [-32., 80., -80., 40., -10., 1.];
+    // (x - 2)^7
+    const P7: [f64; 8] = [-128., 448., -672., 560., -280., 84., -14., 1.];
+
+    #[test]
+    fn near_root() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100000 {
+            let x = 2. + rng.gen_range::<f64>(2f64.powi(-6), 2f64.powi(-4)) *
+                         rng.choose(&[1., -1.]).unwrap();
+            // x - 2 is exact, so this is accurate to a few ulps
+            let expected = (x - 2f64).powi(7);
+            let res = comp_horner(&P7, x);
+            assert!((res - expected).abs() <= 2f64.powi(-48) * expected.abs());
+        }
+    }
+
+    #[test]
+    fn bound() {
+        let mut rng = rand::thread_rng();
+        let coeffs: Vec<f32> = P5.iter().map(|&a| a as f32).collect();
+        for _ in 0..100000 {
+            let d = rng.gen_range::<i32>(-255, 256);
+            let x = 2. + d as f32 * 2f32.powi(-8);
+            // exact in f64
+            let exact = (d as f64 * 2f64.powi(-8)).powi(5);
+            let (res, bound) = comp_horner_with_bound(&coeffs, x);
+            assert!((res as f64 - exact).abs() <= bound as f64);
+            if d.abs() >= 32 {
+                assert!(bound as f64 <= 2f64.powi(-16) * exact.abs());
+            }
+        }
+        assert_eq!(comp_horner::<f64>(&[], 1.), 0.);
+        assert_eq!(comp_horner(&[3.], 1e300), 3.);
+    }
+}
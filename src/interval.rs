@@ -0,0 +1,274 @@
+use core::ops::{Add, Sub, Mul, Div, Neg};
+use traits::FloatEFT;
+use rounding::{add_up, add_down, mul_up, mul_down, div_up, div_down, sqrt_up, sqrt_down};
+
+/// Closed interval `[inf, sup]` of floating point numbers.
+///
+/// Endpoints are rounded outward with the emulated directed rounding (`add_up`, `mul_down`,
+/// etc.), so the result of every operation contains the exact result for all points of the
+/// operands, also near overflow and in the subnormal range. The FPU rounding mode is not
+/// touched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval<T> {
+    inf: T,
+    sup: T,
+}
+
+// NaN arises only from `inf / inf` at the corners of a division, which never attain the
+// bounds. `0 * inf` at a corner of a product is taken as 0, the limit over the finite points.
+#[inline]
+fn min<T: FloatEFT>(a: T, b: T) -> T {
+    if b < a || a.partial_cmp(&a).is_none() { b } else { a }
+}
+
+#[inline]
+fn max<T: FloatEFT>(a: T, b: T) -> T {
+    if b > a || a.partial_cmp(&a).is_none() { b } else { a }
+}
+
+impl<T: FloatEFT> Interval<T> {
+    /// Panics unless `inf <= sup`.
+    #[inline]
+    pub fn new(inf: T, sup: T) -> Interval<T> {
+        assert!(inf <= sup);
+        Interval { inf, sup }
+    }
+
+    #[inline]
+    pub fn from_float(a: T) -> Interval<T> {
+        Interval {
+            inf: a.clone(),
+            sup: a,
+        }
+    }
+
+    /// `[-inf, inf]`
+    #[inline]
+    pub fn entire() -> Interval<T> {
+        Interval {
+            inf: T::neg_infinity(),
+            sup: T::infinity(),
+        }
+    }
+
+    #[inline]
+    pub fn inf(&self) -> T {
+        self.inf.clone()
+    }
+
+    #[inline]
+    pub fn sup(&self) -> T {
+        self.sup.clone()
+    }
+
+    #[inline]
+    pub fn contains(&self, a: T) -> bool {
+        self.inf <= a && a <= self.sup
+    }
+
+    /// Returns `true` if every point of `self` is in `other`.
+    #[inline]
+    pub fn is_subset(&self, other: &Interval<T>) -> bool {
+        other.inf <= self.inf && self.sup <= other.sup
+    }
+
+    /// Smallest interval which contains both `self` and `other`.
+    #[inline]
+    pub fn hull(self, other: Interval<T>) -> Interval<T> {
+        Interval {
+            inf: min(self.inf, other.inf),
+            sup: max(self.sup, other.sup),
+        }
+    }
+
+    /// Returns `None` if `self` and `other` are disjoint.
+    #[inline]
+    pub fn intersection(self, other: Interval<T>) -> Option<Interval<T>> {
+        let (inf, sup) = (max(self.inf, other.inf), min(self.sup, other.sup));
+        if inf <= sup {
+            Some(Interval { inf, sup })
+        } else {
+            None
+        }
+    }
+
+    /// Square root of the non-negative part of `self`. Returns `None` if `self` is
+    /// entirely negative.
+    #[inline]
+    pub fn sqrt(self) -> Option<Interval<T>> {
+        if self.sup < T::zero() {
+            return None;
+        }
+        Some(Interval {
+            inf: sqrt_down(max(self.inf, T::zero())),
+            sup: sqrt_up(self.sup),
+        })
+    }
+}
+
+#[inline]
+fn corner_down<T: FloatEFT>(a: T, b: T) -> T {
+    if a == T::zero() || b == T::zero() { T::zero() } else { mul_down(a, b) }
+}
+
+#[inline]
+fn corner_up<T: FloatEFT>(a: T, b: T) -> T {
+    if a == T::zero() || b == T::zero() { T::zero() } else { mul_up(a, b) }
+}
+
+impl<T: FloatEFT> From<T> for Interval<T> {
+    #[inline]
+    fn from(a: T) -> Interval<T> {
+        Interval::from_float(a)
+    }
+}
+
+impl<T: FloatEFT> Neg for Interval<T> {
+    type Output = Interval<T>;
+    #[inline]
+    fn neg(self) -> Interval<T> {
+        Interval {
+            inf: -self.sup,
+            sup: -self.inf,
+        }
+    }
+}
+
+impl<T: FloatEFT> Add for Interval<T> {
+    type Output = Interval<T>;
+    #[inline]
+    fn add(self, rhs: Interval<T>) -> Interval<T> {
+        Interval {
+            inf: add_down(self.inf, rhs.inf),
+            sup: add_up(self.sup, rhs.sup),
+        }
+    }
+}
+
+impl<T: FloatEFT> Sub for Interval<T> {
+    type Output = Interval<T>;
+    #[inline]
+    fn sub(self, rhs: Interval<T>) -> Interval<T> {
+        self + (-rhs)
+    }
+}
+
+impl<T: FloatEFT> Mul for Interval<T> {
+    type Output = Interval<T>;
+    fn mul(self, rhs: Interval<T>) -> Interval<T> {
+        let (a, b, c, d) = (self.inf, self.sup, rhs.inf, rhs.sup);
+        Interval {
+            inf: min(min(corner_down(a.clone(), c.clone()), corner_down(a.clone(), d.clone())),
+                     min(corner_down(b.clone(), c.clone()), corner_down(b.clone(), d.clone()))),
+            sup: max(max(corner_up(a.clone(), c.clone()), corner_up(a, d.clone())),
+                     max(corner_up(b.clone(), c), corner_up(b, d))),
+        }
+    }
+}
+
+impl<T: FloatEFT> Div for Interval<T> {
+    type Output = Interval<T>;
+    /// Returns `Interval::entire()` if `rhs` contains zero.
+    fn div(self, rhs: Interval<T>) -> Interval<T> {
+        if rhs.contains(T::zero()) {
+            return Interval::entire();
+        }
+        let (a, b, c, d) = (self.inf, self.sup, rhs.inf, rhs.sup);
+        Interval {
+            inf: min(min(div_down(a.clone(), c.clone()), div_down(a.clone(), d.clone())),
+                     min(div_down(b.clone(), c.clone()), div_down(b.clone(), d.clone()))),
+            sup: max(max(div_up(a.clone(), c.clone()), div_up(a, d.clone())),
+                     max(div_up(b.clone(), c), div_up(b, d))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use testutil::{gen_f32, NextFloat};
+
+    fn gen_interval(rng: &mut rand::ThreadRng) -> Interval<f32> {
+        let (a, b) = (gen_f32(rng), gen_f32(rng));
+        if a <= b { Interval::new(a, b) } else { Interval::new(b, a) }
+    }
+
+    fn gen_point(rng: &mut rand::ThreadRng, a: &Interval<f32>) -> f32 {
+        *rng.choose(&[a.inf(), a.sup(), a.inf() / 2. + a.sup() / 2.]).unwrap()
+    }
+
+    #[test]
+    fn enclosure() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let (a, b) = (gen_interval(&mut rng), gen_interval(&mut rng));
+            let (x, y) = (gen_point(&mut rng, &a), gen_point(&mut rng, &b));
+            let (xf, yf) = (x as f64, y as f64);
+            // products of f32 are exact in f64. sums may be rounded, but rounding to f64
+            // never crosses an f32 endpoint.
+            let s = a + b;
+            assert!(s.inf() as f64 <= xf + yf && xf + yf <= s.sup() as f64);
+            let d = a - b;
+            assert!(d.inf() as f64 <= xf - yf && xf - yf <= d.sup() as f64);
+            let p = a * b;
+            assert!(p.inf() as f64 <= xf * yf && xf * yf <= p.sup() as f64);
+            let q = a / b;
+            if y > 0. {
+                assert!(q.inf() as f64 * yf <= xf && xf <= q.sup() as f64 * yf);
+            } else if y < 0. {
+                assert!(q.inf() as f64 * yf >= xf && xf >= q.sup() as f64 * yf);
+            }
+            if let Some(r) = a.sqrt() {
+                let x = x.abs();
+                if a.contains(x) {
+                    let xf = x as f64;
+                    assert!(r.inf() as f64 * r.inf() as f64 <= xf &&
+                            xf <= r.sup() as f64 * r.sup() as f64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_operations() {
+        let (a, b) = (Interval::new(-1., 2.), Interval::new(1., 3.));
+        assert_eq!(a.hull(b), Interval::new(-1., 3.));
+        assert_eq!(a.intersection(b), Some(Interval::new(1., 2.)));
+        assert_eq!(a.intersection(Interval::from(5.)), None);
+        assert!(Interval::new(1., 1.5).is_subset(&a) && !b.is_subset(&a));
+        assert!(a.contains(0.) && !b.contains(0.));
+        assert_eq!(Interval::new(-2., -1.).sqrt(), None);
+        assert_eq!(Interval::new(-2., 4.).sqrt(), Some(Interval::new(0., 2.)));
+        assert_eq!(Interval::from(1.) / a, Interval::entire());
+    }
+
+    #[test]
+    fn extreme() {
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        let max = Interval::from(f64::MAX);
+        assert_eq!(max + max, Interval::new(f64::MAX, f64::INFINITY));
+        assert_eq!(max * Interval::from(-2.), Interval::new(f64::NEG_INFINITY, -f64::MAX));
+        assert_eq!((max + max) * Interval::new(0., 1.), Interval::new(0., f64::INFINITY));
+        let third = Interval::from(1.) / Interval::from(3.);
+        assert_eq!(third, Interval::new(1. / 3., (1f64 / 3.).next_above()));
+        assert_eq!(Interval::from(eta) / Interval::from(3.), Interval::new(0., eta));
+        assert_eq!(Interval::from(eta) * Interval::from(-0.5), Interval::new(-eta, 0.));
+        assert_eq!(Interval::from(3. * eta) - Interval::from(eta), Interval::from(2. * eta));
+        assert_eq!(Interval::from(4. * eta).sqrt(), Some(Interval::from(2f64.powi(-536))));
+        assert_eq!(Interval::from(2. * eta).sqrt(),
+                   Some(Interval::new(2f64.sqrt().next_below(), 2f64.sqrt()) *
+                        Interval::from(2f64.powi(-537))));
+        let tiny = Interval::from(2f64.powi(-600));
+        assert!((tiny * tiny).inf() == 0. && (tiny * tiny).sup() == eta);
+        let zero = Interval::from(0.);
+        assert_eq!(zero * Interval::entire(), zero);
+        assert_eq!(Interval::entire() * zero, zero);
+        assert_eq!(Interval::new(0., 1.) * Interval::new(2., f64::INFINITY),
+                   Interval::new(0., f64::INFINITY));
+    }
+}
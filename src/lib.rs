@@ -21,32 +21,84 @@
 //! and `twoproduct`[2]. The word "safe" means "if output is representable with
 //! normal/subnormal floating point number, the algorithm is mathematically correct."
 //!
+//! On top of them, `add_up`, `mul_down`, etc. emulate directed rounding[3] without
+//! changing the rounding mode of the FPU, and `Interval` is built on them.
+//! `fma_emulated` computes a correctly rounded `a * b + c` without an FMA instruction, and
+//! `err_fma` its exact error. `Expansion` keeps exact sums and products, and the geometric
+//! predicates `orient2d`, `orient3d`, `incircle` and `insphere` return exact signs with it.
+//! `checked_twosum`, `checked_twoproduct`, etc. return an `EftError` instead of a wrong
+//! result when the output is not representable, and `twosum_is_exact`, `split_is_safe`,
+//! `twoproduct_is_exact`, etc. tell cheaply when the faster unsafe algorithms are correct.
+//!
 //! ## Accerelation
-//! With nightly compiler and x86 cpu supporting `fma`, you can accelerate some algorithms
-//! using a `use-fma` feature gate:
+//! `***_fma` variants use `mul_add` of the standard library through the `Fma` trait,
+//! so they are always available. With x86 cpu supporting `fma`, enable the target-feature
+//! to make them fast, and the `use-fma` feature gate to let composite algorithms
+//! (e.g. `dot2`, `comp_horner`) use them. Those algorithms are bounded by `SelectedFma`,
+//! which requires `Fma` only with the feature:
 //! 
 //! `$ RUSTFLAGS='-C target-feature=+fma' cargo build --features use-fma`
 //! 
+//! `safetwosum_straight_f64`, `safesplit_straight_f64`, `safetwoproduct_straight_f64` and
+//! their `f32` versions are explicitly vectorized slice versions, which select AVX or SSE2
+//! kernels at runtime on x86_64.
+//! 
 //! ## References
 //! 1. D. E. Knuth, "The Art of Computer Programming", vol. 2. Addison-Wesley, Reading, MA, 3rd edition, 1998.
 //! 2. T. J. Dekker, "A Floating-Point Technique for Extending the Available Precision", Numer. Math. 18(3), 224-242, 1971.
 //! 3. M. Kashiwagi, "Emulation of Rounded Arithmetic in Rounding to Nearest(Japanese only)", NAS2014, 2014.
 //! 4. American National Standards Institute and Institute of Electrical and Electronic Engineers, "IEEE Standard for Binary Floating-Point Arithmetic", ANSI/IEEE Standard 754-2008, 2008.
 
-#![cfg_attr(feature = "use-fma", feature(cfg_target_feature,intrinsics))]
-
 extern crate core;
-#[cfg(any(feature = "use-fma", feature = "doc"))]
-extern crate fma;
 
 mod traits;
+#[cfg(test)]
+mod testutil;
 
 mod twosum;
 mod split;
 mod twoprod;
+mod twodiv;
+mod twosqrt;
+mod rounding;
+mod fmaemu;
+mod errfma;
+mod threesum;
+mod expansion;
+mod predicates;
+mod checked;
+mod interval;
+mod dispatch;
+mod slice;
+mod simd;
+mod vector;
+mod doubledouble;
+mod sum;
+mod dot;
+mod horner;
+mod accumulator;
 
-pub use traits::FloatEFT;
+pub use traits::{FloatEFT, Fma, SelectedFma};
 
 pub use twosum::*;
 pub use split::*;
 pub use twoprod::*;
+pub use twodiv::*;
+pub use twosqrt::*;
+pub use rounding::*;
+pub use fmaemu::fma_emulated;
+pub use errfma::err_fma;
+pub use threesum::*;
+pub use expansion::Expansion;
+pub use predicates::*;
+pub use checked::*;
+pub use interval::Interval;
+pub use dispatch::{twosum_auto, twoproduct_auto};
+pub use slice::*;
+pub use simd::*;
+pub use vector::{F64x4, F32x8};
+pub use doubledouble::DoubleDouble;
+pub use sum::*;
+pub use dot::*;
+pub use horner::*;
+pub use accumulator::CompensatedAccumulator;
@@ -0,0 +1,472 @@
+use core::cmp::Ordering;
+use traits::{FloatEFT, SelectedFma, is_finite};
+use expansion::Expansion;
+
+// Exact evaluation.
+//
+// A product of floating point numbers is not always representable as an expansion, e.g. it
+// may be below eta, or its components may spread wider than the exponent range. So every
+// coordinate is written as `m * B^k` with `1 <= |m| < B`, and a polynomial of them as
+// `sum_k S_k * B^k`, where `S_k` is an expansion of products of at most five `m`s. With
+// `B^8 <= MAX`, no component of `S_k` overflows or goes below eta.
+
+/// Largest `B = 2^(2^i)` with `B^8 <= MAX`.
+#[inline]
+fn base<T: FloatEFT>() -> T {
+    let mut b = T::radix();
+    loop {
+        let bb = b.clone() * b.clone();
+        let b8 = bb.clone() * bb.clone();
+        let b8 = b8.clone() * b8.clone();
+        if (b8.clone() * b8).is_infinite() {
+            return b;
+        }
+        b = bb;
+    }
+}
+
+/// `sum_k S_k * B^k` as `(k, S_k)` in increasing order of `k`, without zero `S_k`.
+#[derive(Clone)]
+struct Wide<T> {
+    terms: Vec<(i32, Expansion<T>)>,
+}
+
+impl<T: FloatEFT + SelectedFma> Wide<T> {
+    fn from_float(a: T) -> Wide<T> {
+        // the fast filter never decides if a coordinate is not finite, and the scaling
+        // below would not terminate.
+        assert!(is_finite(a.clone()), "coordinates must be finite");
+        if a == T::zero() {
+            return Wide { terms: Vec::new() };
+        }
+        let b = base::<T>();
+        let (mut m, mut k) = (a, 0);
+        // all scalings are exact
+        while m.clone().abs() >= b {
+            m = m / b.clone();
+            k += 1;
+        }
+        while m.clone().abs() < T::one() {
+            m = m * b.clone();
+            k -= 1;
+        }
+        Wide { terms: vec![(k, Expansion::from(m))] }
+    }
+
+    fn add_term(&mut self, k: i32, s: Expansion<T>) {
+        match self.terms.binary_search_by(|t| t.0.cmp(&k)) {
+            Ok(i) => {
+                let t = self.terms[i].1.clone() + s;
+                if t.is_zero() {
+                    self.terms.remove(i);
+                } else {
+                    self.terms[i].1 = t;
+                }
+            }
+            Err(i) => {
+                if !s.is_zero() {
+                    self.terms.insert(i, (k, s));
+                }
+            }
+        }
+    }
+
+    fn add(mut self, rhs: &Wide<T>) -> Wide<T> {
+        for t in &rhs.terms {
+            self.add_term(t.0, t.1.clone());
+        }
+        self
+    }
+
+    fn sub(self, rhs: &Wide<T>) -> Wide<T> {
+        self.add(&rhs.neg())
+    }
+
+    fn neg(&self) -> Wide<T> {
+        Wide { terms: self.terms.iter().map(|t| (t.0, -t.1.clone())).collect() }
+    }
+
+    fn mul(&self, rhs: &Wide<T>) -> Wide<T> {
+        let mut res = Wide { terms: Vec::new() };
+        for a in &self.terms {
+            for b in &rhs.terms {
+                res.add_term(a.0 + b.0, a.1.clone() * b.1.clone());
+            }
+        }
+        res
+    }
+
+    /// Sign of the exact value. While the top term does not dominate the rest, it is
+    /// moved to the next lower level, which does not overflow.
+    fn sign(mut self) -> Ordering {
+        let b = base::<T>();
+        loop {
+            let (k, top) = match self.terms.pop() {
+                Some(t) => t,
+                None => return Ordering::Equal,
+            };
+            if self.terms.is_empty() {
+                return top.sign();
+            }
+            let rest = self.terms.iter().fold(T::zero(), |acc, t| acc + t.1.to_float().abs());
+            let radix = T::radix();
+            if top.to_float().abs() * b.clone() > radix * rest {
+                return top.sign();
+            }
+            self.add_term(k - 1, top.scale(b.clone()));
+        }
+    }
+}
+
+/// `|a|`, or `MIN_POSITIVE` if it is smaller. The rounding error of an operation whose
+/// result is `a` is at most `u * mag(a)`, also when it underflows.
+#[inline]
+fn mag<T: FloatEFT>(a: T) -> T {
+    let a = a.abs();
+    if a < T::min_positive() { T::min_positive() } else { a }
+}
+
+/// Returns the sign of the filtered `det` if `|det| > errbound`.
+#[inline]
+fn filter<T: FloatEFT>(det: T, errbound: T) -> Option<Ordering> {
+    if det > errbound.clone() {
+        Some(Ordering::Greater)
+    } else if -det > errbound {
+        Some(Ordering::Less)
+    } else {
+        None
+    }
+}
+
+// (c + d * u) * u, where u = eps / 2, as in Shewchuk's error bounds
+#[inline]
+fn errbound_coef<T: FloatEFT>(c: u32, d: u32) -> T {
+    let u = T::epsilon() / T::radix();
+    let int = |n: u32| (0..n).fold(T::zero(), |acc, _| acc + T::one());
+    (int(c) + int(d) * u.clone()) * u
+}
+
+/// Returns the sign of the determinant `|pa - pc, pb - pc|`, which is positive if
+/// `pa`, `pb` and `pc` are in counterclockwise order, negative if clockwise and zero if
+/// collinear.
+///
+/// The determinant is first computed in floating point with the error bound of
+/// Shewchuk[1], and only if the result is uncertain is it evaluated exactly with
+/// `Expansion`. The result is exact for all finite inputs, including subnormal and huge
+/// coordinates.
+///
+/// Panics if a coordinate is NaN or infinite.
+///
+/// 1. J. R. Shewchuk, "Adaptive Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates", Discrete Comput. Geom. 18(3), 305-363, 1997.
+pub fn orient2d<T: FloatEFT + SelectedFma>(pa: &[T; 2], pb: &[T; 2], pc: &[T; 2]) -> Ordering {
+    let (acx, acy) = (pa[0].clone() - pc[0].clone(), pa[1].clone() - pc[1].clone());
+    let (bcx, bcy) = (pb[0].clone() - pc[0].clone(), pb[1].clone() - pc[1].clone());
+    let detleft = acx * bcy;
+    let detright = acy * bcx;
+    let det = detleft.clone() - detright.clone();
+    let permanent = mag(detleft) + mag(detright);
+    if let Some(s) = filter(det, errbound_coef::<T>(3, 16) * permanent) {
+        return s;
+    }
+
+    let w = |p: &[T; 2]| (Wide::from_float(p[0].clone()), Wide::from_float(p[1].clone()));
+    let ((ax, ay), (bx, by), (cx, cy)) = (w(pa), w(pb), w(pc));
+    let (acx, acy) = (ax.sub(&cx), ay.sub(&cy));
+    let (bcx, bcy) = (bx.sub(&cx), by.sub(&cy));
+    acx.mul(&bcy).sub(&acy.mul(&bcx)).sign()
+}
+
+/// Returns the sign of the determinant `|pa - pd, pb - pd, pc - pd|`, which is positive if
+/// `pd` lies below the plane through `pa`, `pb` and `pc`, where "below" means that they
+/// appear in counterclockwise order when viewed from above, and zero if the four points
+/// are coplanar.
+///
+/// Evaluated as `orient2d`, so the result is exact for all finite inputs. Panics if a
+/// coordinate is NaN or infinite.
+pub fn orient3d<T: FloatEFT + SelectedFma>(pa: &[T; 3], pb: &[T; 3], pc: &[T; 3], pd: &[T; 3]) -> Ordering {
+    let d = |p: &[T; 3], i: usize| p[i].clone() - pd[i].clone();
+    let (adx, ady, adz) = (d(pa, 0), d(pa, 1), d(pa, 2));
+    let (bdx, bdy, bdz) = (d(pb, 0), d(pb, 1), d(pb, 2));
+    let (cdx, cdy, cdz) = (d(pc, 0), d(pc, 1), d(pc, 2));
+    let bdxcdy = bdx.clone() * cdy.clone();
+    let cdxbdy = cdx.clone() * bdy.clone();
+    let cdxady = cdx * ady.clone();
+    let adxcdy = adx.clone() * cdy;
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+    let det = adz.clone() * (bdxcdy.clone() - cdxbdy.clone()) +
+              bdz.clone() * (cdxady.clone() - adxcdy.clone()) +
+              cdz.clone() * (adxbdy.clone() - bdxady.clone());
+    let permanent = mag((mag(bdxcdy) + mag(cdxbdy)) * adz.abs()) +
+                    mag((mag(cdxady) + mag(adxcdy)) * bdz.abs()) +
+                    mag((mag(adxbdy) + mag(bdxady)) * cdz.abs());
+    if let Some(s) = filter(det, errbound_coef::<T>(7, 56) * permanent) {
+        return s;
+    }
+
+    let w = |p: &[T; 3], i: usize| Wide::from_float(p[i].clone());
+    let d = |p: &[T; 3], i: usize| w(p, i).sub(&w(pd, i));
+    let (adx, ady, adz) = (d(pa, 0), d(pa, 1), d(pa, 2));
+    let (bdx, bdy, bdz) = (d(pb, 0), d(pb, 1), d(pb, 2));
+    let (cdx, cdy, cdz) = (d(pc, 0), d(pc, 1), d(pc, 2));
+    let bc = bdx.mul(&cdy).sub(&cdx.mul(&bdy));
+    let ca = cdx.mul(&ady).sub(&adx.mul(&cdy));
+    let ab = adx.mul(&bdy).sub(&bdx.mul(&ady));
+    adz.mul(&bc).add(&bdz.mul(&ca)).add(&cdz.mul(&ab)).sign()
+}
+
+/// Returns the sign of the determinant which is positive if `pd` lies inside the circle
+/// through `pa`, `pb` and `pc`, negative if outside and zero if the four points are
+/// cocircular. `pa`, `pb` and `pc` must be in counterclockwise order, or the sign is
+/// reversed.
+///
+/// Evaluated as `orient2d`, so the result is exact for all finite inputs. Panics if a
+/// coordinate is NaN or infinite.
+pub fn incircle<T: FloatEFT + SelectedFma>(pa: &[T; 2], pb: &[T; 2], pc: &[T; 2], pd: &[T; 2]) -> Ordering {
+    let d = |p: &[T; 2], i: usize| p[i].clone() - pd[i].clone();
+    let (adx, ady) = (d(pa, 0), d(pa, 1));
+    let (bdx, bdy) = (d(pb, 0), d(pb, 1));
+    let (cdx, cdy) = (d(pc, 0), d(pc, 1));
+    let bdxcdy = bdx.clone() * cdy.clone();
+    let cdxbdy = cdx.clone() * bdy.clone();
+    let cdxady = cdx.clone() * ady.clone();
+    let adxcdy = adx.clone() * cdy.clone();
+    let adxbdy = adx.clone() * bdy.clone();
+    let bdxady = bdx.clone() * ady.clone();
+    let lift = |x: T, y: T| (x.clone() * x.clone() + y.clone() * y.clone(),
+                             mag(x.clone() * x) + mag(y.clone() * y));
+    let (alift, alift_mag) = lift(adx, ady);
+    let (blift, blift_mag) = lift(bdx, bdy);
+    let (clift, clift_mag) = lift(cdx, cdy);
+    let det = alift * (bdxcdy.clone() - cdxbdy.clone()) +
+              blift * (cdxady.clone() - adxcdy.clone()) +
+              clift * (adxbdy.clone() - bdxady.clone());
+    let permanent = mag((mag(bdxcdy) + mag(cdxbdy)) * alift_mag) +
+                    mag((mag(cdxady) + mag(adxcdy)) * blift_mag) +
+                    mag((mag(adxbdy) + mag(bdxady)) * clift_mag);
+    if let Some(s) = filter(det, errbound_coef::<T>(10, 96) * permanent) {
+        return s;
+    }
+
+    let w = |p: &[T; 2], i: usize| Wide::from_float(p[i].clone());
+    let d = |p: &[T; 2], i: usize| w(p, i).sub(&w(pd, i));
+    let (adx, ady) = (d(pa, 0), d(pa, 1));
+    let (bdx, bdy) = (d(pb, 0), d(pb, 1));
+    let (cdx, cdy) = (d(pc, 0), d(pc, 1));
+    let bc = bdx.mul(&cdy).sub(&cdx.mul(&bdy));
+    let ca = cdx.mul(&ady).sub(&adx.mul(&cdy));
+    let ab = adx.mul(&bdy).sub(&bdx.mul(&ady));
+    let lift = |x: &Wide<T>, y: &Wide<T>| x.mul(x).add(&y.mul(y));
+    let (alift, blift, clift) = (lift(&adx, &ady), lift(&bdx, &bdy), lift(&cdx, &cdy));
+    alift.mul(&bc).add(&blift.mul(&ca)).add(&clift.mul(&ab)).sign()
+}
+
+/// Returns the sign of the determinant which is positive if `pe` lies inside the sphere
+/// through `pa`, `pb`, `pc` and `pd`, negative if outside and zero if the five points are
+/// cospherical. `orient3d(pa, pb, pc, pd)` must be positive, or the sign is reversed.
+///
+/// Evaluated as `orient2d`, so the result is exact for all finite inputs. Panics if a
+/// coordinate is NaN or infinite.
+pub fn insphere<T: FloatEFT + SelectedFma>(pa: &[T; 3],
+                             pb: &[T; 3],
+                             pc: &[T; 3],
+                             pd: &[T; 3],
+                             pe: &[T; 3])
+                             -> Ordering {
+    let e = |p: &[T; 3]| {
+        [p[0].clone() - pe[0].clone(),
+         p[1].clone() - pe[1].clone(),
+         p[2].clone() - pe[2].clone()]
+    };
+    let (a, b, c, d) = (e(pa), e(pb), e(pc), e(pd));
+    // (x[0] * y[1] - y[0] * x[1], mag of the permanent)
+    let cross = |x: &[T; 3], y: &[T; 3]| {
+        let (p, q) = (x[0].clone() * y[1].clone(), y[0].clone() * x[1].clone());
+        (p.clone() - q.clone(), mag(p) + mag(q))
+    };
+    let (ab, ab_mag) = cross(&a, &b);
+    let (bc, bc_mag) = cross(&b, &c);
+    let (cd, cd_mag) = cross(&c, &d);
+    let (da, da_mag) = cross(&d, &a);
+    let (ac, ac_mag) = cross(&a, &c);
+    let (bd, bd_mag) = cross(&b, &d);
+    let abc = a[2].clone() * bc.clone() - b[2].clone() * ac.clone() + c[2].clone() * ab.clone();
+    let bcd = b[2].clone() * cd.clone() - c[2].clone() * bd.clone() + d[2].clone() * bc;
+    let cda = c[2].clone() * da.clone() + d[2].clone() * ac + a[2].clone() * cd;
+    let dab = d[2].clone() * ab + a[2].clone() * bd + b[2].clone() * da;
+    let lift = |x: &[T; 3]| {
+        (x[0].clone() * x[0].clone() + x[1].clone() * x[1].clone() + x[2].clone() * x[2].clone(),
+         mag(x[0].clone() * x[0].clone()) + mag(x[1].clone() * x[1].clone()) +
+         mag(x[2].clone() * x[2].clone()))
+    };
+    let ((alift, alift_mag), (blift, blift_mag)) = (lift(&a), lift(&b));
+    let ((clift, clift_mag), (dlift, dlift_mag)) = (lift(&c), lift(&d));
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+    let z = |x: &[T; 3]| x[2].clone().abs();
+    let m3 = |p: T, x: &[T; 3], q: T, y: &[T; 3], r: T, y2: &[T; 3]| {
+        mag(p * z(x)) + mag(q * z(y)) + mag(r * z(y2))
+    };
+    let permanent =
+        mag(m3(cd_mag.clone(), &b, bd_mag.clone(), &c, bc_mag.clone(), &d) * alift_mag) +
+        mag(m3(da_mag.clone(), &c, ac_mag.clone(), &d, cd_mag, &a) * blift_mag) +
+        mag(m3(ab_mag.clone(), &d, bd_mag, &a, da_mag, &b) * clift_mag) +
+        mag(m3(bc_mag, &a, ac_mag, &b, ab_mag, &c) * dlift_mag);
+    if let Some(s) = filter(det, errbound_coef::<T>(16, 224) * permanent) {
+        return s;
+    }
+
+    let e = |p: &[T; 3]| {
+        let w = |i: usize| Wide::from_float(p[i].clone()).sub(&Wide::from_float(pe[i].clone()));
+        [w(0), w(1), w(2)]
+    };
+    let (a, b, c, d) = (e(pa), e(pb), e(pc), e(pd));
+    let cross = |x: &[Wide<T>; 3], y: &[Wide<T>; 3]| x[0].mul(&y[1]).sub(&y[0].mul(&x[1]));
+    let (ab, bc, cd) = (cross(&a, &b), cross(&b, &c), cross(&c, &d));
+    let (da, ac, bd) = (cross(&d, &a), cross(&a, &c), cross(&b, &d));
+    let abc = a[2].mul(&bc).sub(&b[2].mul(&ac)).add(&c[2].mul(&ab));
+    let bcd = b[2].mul(&cd).sub(&c[2].mul(&bd)).add(&d[2].mul(&bc));
+    let cda = c[2].mul(&da).add(&d[2].mul(&ac)).add(&a[2].mul(&cd));
+    let dab = d[2].mul(&ab).add(&a[2].mul(&bd)).add(&b[2].mul(&da));
+    let lift = |x: &[Wide<T>; 3]| x[0].mul(&x[0]).add(&x[1].mul(&x[1])).add(&x[2].mul(&x[2]));
+    let (alift, blift, clift, dlift) = (lift(&a), lift(&b), lift(&c), lift(&d));
+    dlift.mul(&abc)
+        .sub(&clift.mul(&dab))
+        .add(&blift.mul(&cda))
+        .sub(&alift.mul(&bcd))
+        .sign()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use testutil::gen_f32;
+
+    /// Determinant by the Laplace expansion in `Expansion<f64>`, which is exact for
+    /// products of up to five f32 numbers.
+    fn det(m: &[Vec<Expansion<f64>>]) -> Expansion<f64> {
+        if m.len() == 1 {
+            return m[0][0].clone();
+        }
+        let mut res = Expansion::new();
+        for j in 0..m.len() {
+            let minor: Vec<Vec<Expansion<f64>>> = m[1..]
+                .iter()
+                .map(|r| {
+                    r.iter().enumerate().filter(|&(i, _)| i != j).map(|(_, x)| x.clone()).collect()
+                })
+                .collect();
+            let t = m[0][j].clone() * det(&minor);
+            res = if j % 2 == 0 { res + t } else { res - t };
+        }
+        res
+    }
+
+    /// Sign of the determinant with rows `[p, 1]`, or `[p, |p|^2, 1]` if `lift`.
+    fn det_sign(ps: &[&[f32]], lift: bool) -> Ordering {
+        let m: Vec<Vec<Expansion<f64>>> = ps.iter()
+            .map(|p| {
+                let mut r: Vec<Expansion<f64>> =
+                    p.iter().map(|&x| Expansion::from(x as f64)).collect();
+                if lift {
+                    let l = p.iter().fold(Expansion::new(),
+                                          |acc, &x| acc + Expansion::from_product(x as f64, x as f64));
+                    r.push(l);
+                }
+                r.push(Expansion::from(1.));
+                r
+            })
+            .collect();
+        det(&m).sign()
+    }
+
+    // small integers times a common power of two, which are often degenerate
+    fn gen_grid(rng: &mut rand::ThreadRng, scale: f32) -> f32 {
+        rng.gen_range::<i32>(-3, 4) as f32 * scale
+    }
+
+    fn gen_points(rng: &mut rand::ThreadRng, n: usize, grid: bool, max_exp: i32) -> Vec<[f32; 3]> {
+        let scale = 2f32.powi(rng.gen_range(-149, max_exp));
+        let g = |rng: &mut rand::ThreadRng| {
+            if grid { gen_grid(rng, scale) } else { gen_f32(rng) }
+        };
+        (0..n).map(|_| [g(rng), g(rng), g(rng)]).collect()
+    }
+
+    #[test]
+    fn orient_f32() {
+        let mut rng = rand::thread_rng();
+        for i in 0..100000 {
+            let p = gen_points(&mut rng, 4, i % 2 == 1, 126);
+            let q: Vec<[f32; 2]> = p.iter().map(|x| [x[0], x[1]]).collect();
+            let rows: Vec<&[f32]> = q[..3].iter().map(|x| &x[..]).collect();
+            assert_eq!(orient2d(&q[0], &q[1], &q[2]), det_sign(&rows, false), "{:?}", q);
+            let rows: Vec<&[f32]> = p.iter().map(|x| &x[..]).collect();
+            assert_eq!(orient3d(&p[0], &p[1], &p[2], &p[3]), det_sign(&rows, false), "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn in_f32() {
+        let mut rng = rand::thread_rng();
+        for i in 0..10000 {
+            let p = gen_points(&mut rng, 5, i % 2 == 1, 60);
+            let q: Vec<[f32; 2]> = p.iter().map(|x| [x[0], x[1]]).collect();
+            let rows: Vec<&[f32]> = q[..4].iter().map(|x| &x[..]).collect();
+            assert_eq!(incircle(&q[0], &q[1], &q[2], &q[3]), det_sign(&rows, true), "{:?}", q);
+            let rows: Vec<&[f32]> = p.iter().map(|x| &x[..]).collect();
+            assert_eq!(insphere(&p[0], &p[1], &p[2], &p[3], &p[4]),
+                       det_sign(&rows, true),
+                       "{:?}",
+                       p);
+        }
+    }
+
+    #[test]
+    fn extreme() {
+        let (max, eta) = (f64::MAX, 2f64.powi(-1022) * 2f64.powi(-52));
+        assert_eq!(orient2d(&[1e300, 0.], &[-1e300, 0.], &[0., 1e-320]), Ordering::Less);
+        assert_eq!(orient2d(&[max, max], &[-max, -max], &[0., 0.]), Ordering::Equal);
+        assert_eq!(orient2d(&[max, max], &[-max, -max], &[0., eta]), Ordering::Less);
+        assert_eq!(orient2d(&[eta, 0.], &[0., eta], &[0., 0.]), Ordering::Greater);
+        assert_eq!(orient2d(&[3. * eta, eta], &[eta, 3. * eta], &[2. * eta, 2. * eta]),
+                   Ordering::Equal);
+        let (a, b, c) = ([0., 0., 0.], [1., 0., 0.], [0., 1., 0.]);
+        assert_eq!(orient3d(&a, &b, &c, &[0., 0., -eta]), Ordering::Greater);
+        assert_eq!(orient3d(&a, &b, &c, &[max, -max, eta]), Ordering::Less);
+        assert_eq!(orient3d(&a, &b, &c, &[max, max, 0.]), Ordering::Equal);
+        let s = 2f64.powi(-1022) * 2f64.powi(-38);
+        let circle = [[5. * s, 0.], [0., 5. * s], [-5. * s, 0.]];
+        assert_eq!(incircle(&circle[0], &circle[1], &circle[2], &[3. * s, -4. * s]),
+                   Ordering::Equal);
+        assert_eq!(incircle(&circle[0], &circle[1], &circle[2], &[3. * s, -4. * s + eta]),
+                   Ordering::Greater);
+        assert_eq!(incircle(&circle[0], &circle[1], &circle[2], &[1e300, 0.]), Ordering::Less);
+        let h = 2f64.powi(1000);
+        let sphere = [[0., h, 0.], [h, 0., 0.], [-h, 0., 0.], [0., 0., h]];
+        assert_eq!(orient3d(&sphere[0], &sphere[1], &sphere[2], &sphere[3]), Ordering::Greater);
+        let ins = |e: [f64; 3]| insphere(&sphere[0], &sphere[1], &sphere[2], &sphere[3], &e);
+        assert_eq!(ins([0., -h, 0.]), Ordering::Equal);
+        assert_eq!(ins([0., 0., 0.]), Ordering::Greater);
+        assert_eq!(ins([eta, -h, 0.]), Ordering::Less);
+        assert_eq!(ins([eta, -h * (1. - f64::EPSILON / 2.), eta]), Ordering::Greater);
+        assert_eq!(ins([0., 0., -max]), Ordering::Less);
+    }
+
+    #[test]
+    #[should_panic]
+    fn infinite_coordinate() {
+        orient2d(&[f64::INFINITY, 0.], &[0., 1.], &[0., 0.]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nan_coordinate() {
+        let (a, b, c, d) = ([0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]);
+        insphere(&a, &b, &c, &d, &[f32::NAN, 0., 0.]);
+    }
+}
@@ -0,0 +1,346 @@
+use core::cmp::Ordering;
+use traits::FloatEFT;
+use twosum::safetwosum_straight;
+use twoprod::safetwoproduct_straight;
+use twosqrt::safetwosqrt;
+
+/// Returns the smallest floating point number greater than `a`, computed in
+/// round-to-nearest as in Kashiwagi[1]. `succ(MAX) == inf` and `succ(-inf) == -MAX`.
+///
+/// 1. M. Kashiwagi, "Emulation of Rounded Arithmetic in Rounding to Nearest(Japanese only)", NAS2014, 2014.
+#[inline]
+pub fn succ<T: FloatEFT>(a: T) -> T {
+    let u = T::epsilon() / T::radix();
+    let phi = u.clone() + u * T::epsilon(); // u * (1 + 2u), which is representable
+    let abs_a = a.clone().abs();
+    if abs_a >= T::radix() * T::min_positive() / T::epsilon() {
+        if a == T::neg_infinity() {
+            -T::max_value()
+        } else {
+            a + abs_a * phi // ulp(a) / 2 < abs_a * phi < 3 * ulp(a) / 2
+        }
+    } else if abs_a < T::radix() * T::min_positive() {
+        a + T::unit_underflow() // ulp(a) == eta
+    } else {
+        // abs_a * phi underflows, so scale a by 2^p. all scalings are exact.
+        let c = a * (T::radix() / T::epsilon());
+        (c.clone() + c.abs() * phi) * (T::epsilon() / T::radix())
+    }
+}
+
+/// Returns the largest floating point number less than `a`. `pred(-MAX) == -inf` and
+/// `pred(inf) == MAX`.
+#[inline]
+pub fn pred<T: FloatEFT>(a: T) -> T {
+    -succ(-a)
+}
+
+// Each `***_cmp` returns the nearest rounding `res` of the exact result and how the exact
+// result compares to `res`.
+
+#[inline]
+fn round_up<T: FloatEFT>((res, ord): (T, Ordering)) -> T {
+    if ord == Ordering::Greater { succ(res) } else { res }
+}
+
+#[inline]
+fn round_down<T: FloatEFT>((res, ord): (T, Ordering)) -> T {
+    if ord == Ordering::Less { pred(res) } else { res }
+}
+
+/// `err` must have the sign of the exact result minus `res`, unless `res` overflowed.
+#[inline]
+fn compare<T: FloatEFT>(res: T, err: T) -> (T, Ordering) {
+    let ord = if res == T::infinity() {
+        Ordering::Less
+    } else if res == T::neg_infinity() {
+        Ordering::Greater
+    } else {
+        err.partial_cmp(&T::zero()).unwrap_or(Ordering::Equal)
+    };
+    (res, ord)
+}
+
+#[inline]
+fn add_cmp<T: FloatEFT>(a: T, b: T) -> (T, Ordering) {
+    if a.is_infinite() || b.is_infinite() {
+        return (a + b, Ordering::Equal);
+    }
+    let (s, e) = safetwosum_straight(a, b);
+    compare(s, e)
+}
+
+#[inline]
+fn mul_cmp<T: FloatEFT>(a: T, b: T) -> (T, Ordering) {
+    if a.is_infinite() || b.is_infinite() {
+        return (a * b, Ordering::Equal);
+    }
+    let (p, e) = safetwoproduct_straight(a.clone(), b.clone());
+    if p.clone().abs() >= T::radix() * T::radix() * T::min_positive() / T::epsilon() {
+        // the error is representable.
+        compare(p, e)
+    } else {
+        // the error may underflow. scale the smaller one by 1/min_positive and the other
+        // by 1/eps, then the error is representable unless a * b == 0 and nothing
+        // overflows. all scalings are exact.
+        let (small, large) = if a.clone().abs() <= b.clone().abs() { (a, b) } else { (b, a) };
+        let (s, t) = safetwoproduct_straight(small / T::min_positive(), large / T::epsilon());
+        let pc = p.clone() / T::min_positive() / T::epsilon();
+        let ord = match s.partial_cmp(&pc) {
+            Some(Ordering::Equal) => t.partial_cmp(&T::zero()).unwrap_or(Ordering::Equal),
+            Some(ord) => ord,
+            None => Ordering::Equal,
+        };
+        (p, ord)
+    }
+}
+
+#[inline]
+fn div_cmp<T: FloatEFT>(a: T, b: T) -> (T, Ordering) {
+    let q = a.clone() / b.clone();
+    if a.is_infinite() || b.is_infinite() || a == T::zero() || b == T::zero() {
+        return (q, Ordering::Equal);
+    }
+    if q.is_infinite() {
+        return compare(q, T::zero());
+    }
+    if q == T::zero() {
+        let ord = if (a > T::zero()) == (b > T::zero()) {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+        return (q, ord);
+    }
+    // a / b == (c * a) / (c * b) for any power of two c. choose c so that q * (c * b) has
+    // a representable error and does not overflow. all scalings are exact.
+    let abs_a = a.clone().abs();
+    let (aa, bb) = if abs_a <
+                      T::radix() * T::radix() * T::radix() * T::min_positive() / T::epsilon() {
+        let c = T::radix() * (T::radix() / T::epsilon()) * (T::radix() / T::epsilon());
+        (a * c.clone(), b * c)
+    } else if abs_a >= T::max_value() / T::radix() {
+        (a / T::radix(), b / T::radix()) // |b| >= 1/2 since q does not overflow
+    } else {
+        (a, b)
+    };
+    let (p, e) = safetwoproduct_straight(q.clone(), bb.clone());
+    let r = (aa - p) - e; // sign of aa - q * bb. aa - p is exact
+    let ord = if bb > T::zero() {
+        r.partial_cmp(&T::zero())
+    } else {
+        T::zero().partial_cmp(&r)
+    };
+    (q, ord.unwrap_or(Ordering::Equal))
+}
+
+#[inline]
+fn sqrt_cmp<T: FloatEFT>(a: T) -> (T, Ordering) {
+    let s = a.clone().sqrt();
+    if a.partial_cmp(&T::zero()) != Some(Ordering::Greater) || a.is_infinite() {
+        return (s, Ordering::Equal);
+    }
+    // sqrt(c^2 * a) == c * sqrt(a) for any power of two c. scale small a so that the
+    // remainder is representable.
+    let aa = if a < T::radix() * T::radix() * T::radix() * T::min_positive() / T::epsilon() {
+        let c = T::radix() * T::radix() / T::epsilon();
+        a * c.clone() * c
+    } else {
+        a
+    };
+    let (_, r) = safetwosqrt(aa);
+    (s, r.partial_cmp(&T::zero()).unwrap_or(Ordering::Equal))
+}
+
+/// Returns `a + b` rounded toward positive infinity.
+#[inline]
+pub fn add_up<T: FloatEFT>(a: T, b: T) -> T {
+    round_up(add_cmp(a, b))
+}
+
+/// Returns `a + b` rounded toward negative infinity.
+#[inline]
+pub fn add_down<T: FloatEFT>(a: T, b: T) -> T {
+    round_down(add_cmp(a, b))
+}
+
+#[inline]
+pub fn sub_up<T: FloatEFT>(a: T, b: T) -> T {
+    round_up(add_cmp(a, -b))
+}
+
+#[inline]
+pub fn sub_down<T: FloatEFT>(a: T, b: T) -> T {
+    round_down(add_cmp(a, -b))
+}
+
+#[inline]
+pub fn mul_up<T: FloatEFT>(a: T, b: T) -> T {
+    round_up(mul_cmp(a, b))
+}
+
+#[inline]
+pub fn mul_down<T: FloatEFT>(a: T, b: T) -> T {
+    round_down(mul_cmp(a, b))
+}
+
+#[inline]
+pub fn div_up<T: FloatEFT>(a: T, b: T) -> T {
+    round_up(div_cmp(a, b))
+}
+
+#[inline]
+pub fn div_down<T: FloatEFT>(a: T, b: T) -> T {
+    round_down(div_cmp(a, b))
+}
+
+#[inline]
+pub fn sqrt_up<T: FloatEFT>(a: T) -> T {
+    round_up(sqrt_cmp(a))
+}
+
+#[inline]
+pub fn sqrt_down<T: FloatEFT>(a: T) -> T {
+    round_down(sqrt_cmp(a))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use testutil::NextFloat;
+
+    fn gen_f32(rng: &mut rand::ThreadRng) -> f32 {
+        let x = f32::from_bits(rng.gen::<u32>());
+        if x.is_finite() { x } else { 1. }
+    }
+
+    // how the exact result compares to the f32 `r`. every product of f32 is exact in f64.
+    fn exact_cmp_add(a: f32, b: f32, r: f32) -> Ordering {
+        let s = a as f64 + b as f64;
+        let e = (a as f64 - (s - (s - a as f64))) + (b as f64 - (s - a as f64));
+        match s.partial_cmp(&(r as f64)).unwrap() {
+            Ordering::Equal => e.partial_cmp(&0.).unwrap(),
+            ord => ord,
+        }
+    }
+
+    fn check_rounding(up: f32, down: f32, cmp: &dyn Fn(f32) -> Ordering) {
+        assert!(cmp(up) != Ordering::Greater && cmp(down) != Ordering::Less);
+        if up.is_finite() && up != -f32::MAX {
+            assert_eq!(cmp(up.next_below()), Ordering::Greater);
+        }
+        if down.is_finite() && down != f32::MAX {
+            assert_eq!(cmp(down.next_above()), Ordering::Less);
+        }
+        assert!(up == down || up == down.next_above() ||
+                (up == -f32::MAX && down == f32::NEG_INFINITY) ||
+                (up == f32::INFINITY && down == f32::MAX));
+    }
+
+    #[test]
+    fn succ_pred() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            let a = gen_f32(&mut rng);
+            assert_eq!(succ(a), a.next_above());
+            assert_eq!(pred(a), a.next_below());
+        }
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        for &a in &[0., f64::MAX, -f64::MAX, eta, f64::MIN_POSITIVE, 1., -1., 2f64.powi(-969),
+                    2f64.powi(-970), 2f64.powi(-1021)] {
+            assert_eq!(succ(a), a.next_above());
+            assert_eq!(pred(a), a.next_below());
+        }
+        assert_eq!(succ(f64::NEG_INFINITY), -f64::MAX);
+        assert_eq!(pred(f64::INFINITY), f64::MAX);
+        assert_eq!(succ(f64::INFINITY), f64::INFINITY);
+    }
+
+    #[test]
+    fn add_mul() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            let (a, b) = (gen_f32(&mut rng), gen_f32(&mut rng));
+            check_rounding(add_up(a, b), add_down(a, b), &|r| exact_cmp_add(a, b, r));
+            check_rounding(sub_up(a, b), sub_down(a, b), &|r| exact_cmp_add(a, -b, r));
+            let p = a as f64 * b as f64;
+            check_rounding(mul_up(a, b),
+                           mul_down(a, b),
+                           &|r| p.partial_cmp(&(r as f64)).unwrap());
+        }
+        assert_eq!(add_down(f32::MAX, f32::MAX), f32::MAX);
+        assert_eq!(add_up(f32::MAX, f32::MAX), f32::INFINITY);
+        assert_eq!(mul_up(-f32::MAX, 2.), -f32::MAX);
+        assert_eq!(mul_up(f32::INFINITY, 2.), f32::INFINITY);
+        assert_eq!(mul_down(f32::INFINITY, 2.), f32::INFINITY);
+        let eta = 2f32.powi(-126) * 2f32.powi(-23);
+        assert_eq!(mul_up(2f32.powi(-75), 2f32.powi(-75)), eta);
+        assert_eq!(mul_down(2f32.powi(-75), -2f32.powi(-75)), -eta);
+        assert_eq!(mul_up(2f32.powi(-75), -2f32.powi(-75)), 0.);
+    }
+
+    #[test]
+    fn div_sqrt() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            let (a, b) = (gen_f32(&mut rng), gen_f32(&mut rng));
+            if b != 0. {
+                // a / b vs r <=> a vs r * b if b > 0
+                let cmp = |r: f32| if r.is_infinite() {
+                    if r > 0. { Ordering::Less } else { Ordering::Greater }
+                } else if b > 0. {
+                    (a as f64).partial_cmp(&(r as f64 * b as f64)).unwrap()
+                } else {
+                    (r as f64 * b as f64).partial_cmp(&(a as f64)).unwrap()
+                };
+                check_rounding(div_up(a, b), div_down(a, b), &cmp);
+            }
+            let a = a.abs();
+            let cmp = |r: f32| if r < 0. {
+                Ordering::Greater
+            } else {
+                (a as f64).partial_cmp(&(r as f64 * r as f64)).unwrap()
+            };
+            check_rounding(sqrt_up(a), sqrt_down(a), &cmp);
+        }
+        assert_eq!(div_down(f32::MAX, 0.5), f32::MAX);
+        assert_eq!(div_up(1., 0.), f32::INFINITY);
+        let eta = 2f32.powi(-126) * 2f32.powi(-23);
+        assert_eq!(div_up(eta, 4.), eta);
+        assert_eq!(div_down(eta, 4.), 0.);
+        assert_eq!(div_down(-eta, f32::MAX), -eta);
+        assert!(sqrt_up(-1f32).is_nan());
+    }
+
+    #[test]
+    fn f64_edge() {
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        assert_eq!(add_up(1., eta), 1f64.next_above());
+        assert_eq!(add_down(1., eta), 1.);
+        assert_eq!(sub_down(1., eta), 1f64.next_below());
+        assert_eq!(add_up(f64::MAX, eta), f64::INFINITY);
+        assert_eq!(add_down(f64::MAX, f64::MAX), f64::MAX);
+        assert_eq!(mul_up(1. + f64::EPSILON, 1. + f64::EPSILON),
+                   (1. + 2. * f64::EPSILON).next_above());
+        assert_eq!(mul_down(f64::MAX, 1. + f64::EPSILON), f64::MAX);
+        assert_eq!(mul_up(2f64.powi(-537), 2f64.powi(-538)), eta);
+        assert_eq!(mul_down(3. * eta, 0.5), eta);
+        assert_eq!(mul_up(3. * eta, 0.5), 2. * eta);
+        assert_eq!(div_up(1., 3.), (1f64 / 3.).next_above());
+        assert_eq!(div_down(1., 3.), 1f64 / 3.);
+        assert_eq!(div_down(f64::MAX, 1. - f64::EPSILON / 2.), f64::MAX);
+        assert_eq!(div_up(eta, 3.), eta);
+        assert_eq!(div_down(eta, -3.), -eta);
+        assert_eq!(sqrt_up(2.), 2f64.sqrt());
+        assert_eq!(sqrt_down(2.), 2f64.sqrt().next_below());
+        assert_eq!(sqrt_down(f64::MAX), f64::MAX.sqrt());
+        assert_eq!(sqrt_up(f64::MAX), f64::MAX.sqrt().next_above());
+        assert_eq!(sqrt_up(eta), 2f64.powi(-537));
+        assert_eq!(sqrt_down(2. * eta), 2f64.sqrt().next_below() * 2f64.powi(-537));
+    }
+}
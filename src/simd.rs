@@ -0,0 +1,310 @@
+macro_rules! impl_kernels {
+    ($m:ident, $feature:tt, $t:ident, $v:ty, $lanes:expr,
+     $set1:ident, $loadu:ident, $storeu:ident,
+     $add:ident, $sub:ident, $mul:ident, $div:ident, $xor:ident, $or:ident) => (
+        #[cfg(target_arch = "x86_64")]
+        mod $m {
+            use std::arch::x86_64::*;
+            use std::$t;
+            use traits::FloatEFT;
+            use slice;
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn neg(a: $v) -> $v {
+                $xor(a, $set1(-0.))
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn twosum(x: $v, y: $v) -> ($v, $v) {
+                let sum = $add(x, y);
+                let tmp = $sub(sum, x);
+                (sum, $add($sub(x, $sub(sum, tmp)), $sub(y, tmp)))
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn fasttwosum(x: $v, y: $v) -> ($v, $v) {
+                let sum = $add(x, y);
+                (sum, $sub(y, $sub(sum, x)))
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn split(a: $v) -> ($v, $v) {
+                let tmp = $mul(a, $set1(<$t as FloatEFT>::split_coef()));
+                let x = $sub(tmp, $sub(tmp, a));
+                (x, $sub(a, x))
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn safetwosum_straight(x: $v, y: $v) -> ($v, $v) {
+                let two = $set1(2.);
+                let s = $add(x, y);
+                let (xx, yy) = ($div(x, two), $div(y, two));
+                let err_uf = $add($sub(x, $mul(xx, two)), $sub(y, $mul(yy, two)));
+                let (ss, ee) = twosum(xx, yy);
+                let err_h = $sub($mul(two, ss), s);
+                (s, $add($add($mul(two, ee), err_h), err_uf))
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn safesplit_straight(a: $v) -> ($v, $v, $v) {
+                let two = $set1(2.);
+                let min_positive = $set1($t::MIN_POSITIVE);
+                let aa = $div(a, two);
+                let err = $sub(a, $mul(aa, two));
+                let naa = $or(aa, $set1(-0.));
+                let step = $add($div($div($sub($add(naa, min_positive), naa), min_positive),
+                                     $set1($t::EPSILON * $t::EPSILON * $t::EPSILON)),
+                                $set1($t::EPSILON));
+                let split_shift = split($mul(aa, step));
+                ($div(split_shift.0, step), $div(split_shift.1, step), err)
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn safetwoproduct_straight(a: $v, b: $v) -> ($v, $v) {
+                let (two, four) = ($set1(2.), $set1(4.));
+                let prod = $mul(a, b);
+                let ((a1, a2, a3), (b1, b2, b3)) = (safesplit_straight(a), safesplit_straight(b));
+                let two_a1b1 = $mul(two, $mul(a1, b1));
+                let mid = fasttwosum(prod, neg(two_a1b1));
+                let t = $sub($add($sub(mid.0, two_a1b1), mid.1), $mul($mul(four, b2), a1));
+                let t = $sub(t, $mul($mul(four, a2), b1));
+                (prod, $add($add($sub($mul($mul(four, a2), b2), t), $mul(a, b3)), $mul(b, a3)))
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn safetwosum_straight_slice(x: &[$t], y: &[$t], hi: &mut [$t], lo: &mut [$t]) {
+                let body = x.len() - x.len() % $lanes;
+                let mut i = 0;
+                while i < body {
+                    let (s, e) = safetwosum_straight($loadu(x.as_ptr().add(i)),
+                                                     $loadu(y.as_ptr().add(i)));
+                    $storeu(hi.as_mut_ptr().add(i), s);
+                    $storeu(lo.as_mut_ptr().add(i), e);
+                    i += $lanes;
+                }
+                slice::safetwosum_straight_slice(&x[body..], &y[body..],
+                                                 &mut hi[body..], &mut lo[body..]);
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn safetwoproduct_straight_slice(a: &[$t], b: &[$t], hi: &mut [$t], lo: &mut [$t]) {
+                let body = a.len() - a.len() % $lanes;
+                let mut i = 0;
+                while i < body {
+                    let (p, e) = safetwoproduct_straight($loadu(a.as_ptr().add(i)),
+                                                         $loadu(b.as_ptr().add(i)));
+                    $storeu(hi.as_mut_ptr().add(i), p);
+                    $storeu(lo.as_mut_ptr().add(i), e);
+                    i += $lanes;
+                }
+                slice::safetwoproduct_straight_slice(&a[body..], &b[body..],
+                                                     &mut hi[body..], &mut lo[body..]);
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn safesplit_straight_slice(a: &[$t], high: &mut [$t], low: &mut [$t], err: &mut [$t]) {
+                let body = a.len() - a.len() % $lanes;
+                let mut i = 0;
+                while i < body {
+                    let (h, l, e) = safesplit_straight($loadu(a.as_ptr().add(i)));
+                    $storeu(high.as_mut_ptr().add(i), h);
+                    $storeu(low.as_mut_ptr().add(i), l);
+                    $storeu(err.as_mut_ptr().add(i), e);
+                    i += $lanes;
+                }
+                slice::safesplit_straight_slice(&a[body..], &mut high[body..],
+                                                &mut low[body..], &mut err[body..]);
+            }
+        }
+    )
+}
+
+impl_kernels!(sse2_f64, "sse2", f64, __m128d, 2,
+              _mm_set1_pd, _mm_loadu_pd, _mm_storeu_pd,
+              _mm_add_pd, _mm_sub_pd, _mm_mul_pd, _mm_div_pd, _mm_xor_pd, _mm_or_pd);
+impl_kernels!(avx_f64, "avx", f64, __m256d, 4,
+              _mm256_set1_pd, _mm256_loadu_pd, _mm256_storeu_pd,
+              _mm256_add_pd, _mm256_sub_pd, _mm256_mul_pd, _mm256_div_pd,
+              _mm256_xor_pd, _mm256_or_pd);
+impl_kernels!(sse2_f32, "sse2", f32, __m128, 4,
+              _mm_set1_ps, _mm_loadu_ps, _mm_storeu_ps,
+              _mm_add_ps, _mm_sub_ps, _mm_mul_ps, _mm_div_ps, _mm_xor_ps, _mm_or_ps);
+impl_kernels!(avx_f32, "avx", f32, __m256, 8,
+              _mm256_set1_ps, _mm256_loadu_ps, _mm256_storeu_ps,
+              _mm256_add_ps, _mm256_sub_ps, _mm256_mul_ps, _mm256_div_ps,
+              _mm256_xor_ps, _mm256_or_ps);
+
+macro_rules! impl_binary {
+    ($(#[$attr:meta])* $name:ident, $t:ident, $sse2:ident, $avx:ident, $kernel:ident) => (
+        $(#[$attr])*
+        ///
+        /// Uses the AVX kernel if the running cpu supports it and the SSE2 kernel otherwise on
+        /// x86_64, and the scalar implementation on other architectures. Results are bitwise
+        /// identical to the scalar function.
+        pub fn $name(x: &[$t], y: &[$t], hi: &mut [$t], lo: &mut [$t]) {
+            assert!(x.len() == y.len() && x.len() == hi.len() && x.len() == lo.len());
+            #[cfg(target_arch = "x86_64")]
+            {
+                if is_x86_feature_detected!("avx") {
+                    unsafe { $avx::$kernel(x, y, hi, lo) }
+                } else {
+                    unsafe { $sse2::$kernel(x, y, hi, lo) }
+                }
+            }
+            #[cfg(not(target_arch = "x86_64"))]
+            {
+                ::slice::$kernel(x, y, hi, lo)
+            }
+        }
+    )
+}
+
+macro_rules! impl_split {
+    ($(#[$attr:meta])* $name:ident, $t:ident, $sse2:ident, $avx:ident) => (
+        $(#[$attr])*
+        ///
+        /// Uses the AVX kernel if the running cpu supports it and the SSE2 kernel otherwise on
+        /// x86_64, and the scalar implementation on other architectures. Results are bitwise
+        /// identical to the scalar function.
+        pub fn $name(a: &[$t], high: &mut [$t], low: &mut [$t], err: &mut [$t]) {
+            assert!(a.len() == high.len() && a.len() == low.len() && a.len() == err.len());
+            #[cfg(target_arch = "x86_64")]
+            {
+                if is_x86_feature_detected!("avx") {
+                    unsafe { $avx::safesplit_straight_slice(a, high, low, err) }
+                } else {
+                    unsafe { $sse2::safesplit_straight_slice(a, high, low, err) }
+                }
+            }
+            #[cfg(not(target_arch = "x86_64"))]
+            {
+                ::slice::safesplit_straight_slice(a, high, low, err)
+            }
+        }
+    )
+}
+
+impl_binary!(/// Same as `safetwosum_straight_slice` for `f64`.
+             safetwosum_straight_f64, f64, sse2_f64, avx_f64, safetwosum_straight_slice);
+impl_binary!(/// Same as `safetwosum_straight_slice` for `f32`.
+             safetwosum_straight_f32, f32, sse2_f32, avx_f32, safetwosum_straight_slice);
+impl_binary!(/// Same as `safetwoproduct_straight_slice` for `f64`.
+             safetwoproduct_straight_f64, f64, sse2_f64, avx_f64, safetwoproduct_straight_slice);
+impl_binary!(/// Same as `safetwoproduct_straight_slice` for `f32`.
+             safetwoproduct_straight_f32, f32, sse2_f32, avx_f32, safetwoproduct_straight_slice);
+impl_split!(/// Same as `safesplit_straight_slice` for `f64`.
+            safesplit_straight_f64, f64, sse2_f64, avx_f64);
+impl_split!(/// Same as `safesplit_straight_slice` for `f32`.
+            safesplit_straight_f32, f32, sse2_f32, avx_f32);
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use self::rand::Rng;
+
+    use super::*;
+    use twosum::safetwosum_straight;
+    use split::safesplit_straight;
+    use twoprod::safetwoproduct_straight;
+
+    fn gen_f64(rng: &mut rand::ThreadRng) -> f64 {
+        let ranges = [(-1074, -1000), (-510, 510), (1000, 1024)];
+        let (lo, hi) = *rng.choose(&ranges).unwrap();
+        let e = rng.gen_range(lo, hi);
+        rng.gen_range::<f64>(1., 2.) * 2f64.powi(e / 2) * 2f64.powi(e - e / 2) *
+        rng.choose(&[1., -1.]).unwrap()
+    }
+
+    fn gen_f32(rng: &mut rand::ThreadRng) -> f32 {
+        let ranges = [(-149, -120), (-60, 60), (120, 128)];
+        let (lo, hi) = *rng.choose(&ranges).unwrap();
+        let e = rng.gen_range(lo, hi);
+        (rng.gen_range::<f64>(1., 2.) * 2f64.powi(e)) as f32 * rng.choose(&[1., -1.]).unwrap()
+    }
+
+    fn same(a: f64, b: f64) -> bool {
+        a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+    }
+
+    fn same32(a: f32, b: f32) -> bool {
+        a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+    }
+
+    #[test]
+    fn f64_same_as_scalar() {
+        let mut rng = rand::thread_rng();
+        for n in 0..100 {
+            let x: Vec<f64> = (0..n).map(|_| gen_f64(&mut rng)).collect();
+            let y: Vec<f64> = (0..n).map(|_| gen_f64(&mut rng)).collect();
+            let (mut hi, mut lo, mut err) = (vec![0.; n], vec![0.; n], vec![0.; n]);
+
+            safetwosum_straight_f64(&x, &y, &mut hi, &mut lo);
+            for i in 0..n {
+                let (h, l) = safetwosum_straight(x[i], y[i]);
+                assert!(same(hi[i], h) && same(lo[i], l));
+            }
+            safetwoproduct_straight_f64(&x, &y, &mut hi, &mut lo);
+            for i in 0..n {
+                let (h, l) = safetwoproduct_straight(x[i], y[i]);
+                assert!(same(hi[i], h) && same(lo[i], l));
+            }
+            safesplit_straight_f64(&x, &mut hi, &mut lo, &mut err);
+            for i in 0..n {
+                let (h, l, e) = safesplit_straight(x[i]);
+                assert!(same(hi[i], h) && same(lo[i], l) && same(err[i], e));
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                unsafe { super::sse2_f64::safetwoproduct_straight_slice(&x, &y, &mut hi, &mut lo) };
+                for i in 0..n {
+                    let (h, l) = safetwoproduct_straight(x[i], y[i]);
+                    assert!(same(hi[i], h) && same(lo[i], l));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn f32_same_as_scalar() {
+        let mut rng = rand::thread_rng();
+        for n in 0..100 {
+            let x: Vec<f32> = (0..n).map(|_| gen_f32(&mut rng)).collect();
+            let y: Vec<f32> = (0..n).map(|_| gen_f32(&mut rng)).collect();
+            let (mut hi, mut lo, mut err) = (vec![0.; n], vec![0.; n], vec![0.; n]);
+
+            safetwosum_straight_f32(&x, &y, &mut hi, &mut lo);
+            for i in 0..n {
+                let (h, l) = safetwosum_straight(x[i], y[i]);
+                assert!(same32(hi[i], h) && same32(lo[i], l));
+            }
+            safetwoproduct_straight_f32(&x, &y, &mut hi, &mut lo);
+            for i in 0..n {
+                let (h, l) = safetwoproduct_straight(x[i], y[i]);
+                assert!(same32(hi[i], h) && same32(lo[i], l));
+            }
+            safesplit_straight_f32(&x, &mut hi, &mut lo, &mut err);
+            for i in 0..n {
+                let (h, l, e) = safesplit_straight(x[i]);
+                assert!(same32(hi[i], h) && same32(lo[i], l) && same32(err[i], e));
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                unsafe { super::sse2_f32::safesplit_straight_slice(&x, &mut hi, &mut lo, &mut err) };
+                for i in 0..n {
+                    let (h, l, e) = safesplit_straight(x[i]);
+                    assert!(same32(hi[i], h) && same32(lo[i], l) && same32(err[i], e));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use traits::{FloatEFT, Fma};
+use twosum::{safetwosum_straight, safetwosum_fma};
+use split::safesplit_straight;
+use twoprod::{safetwoproduct_straight, safetwoproduct_fma};
+
+// Every function below is a plain zipped loop without branches, so that the compiler can
+// vectorize the branchless scalar algorithm. All slices must have the same length.
+
+macro_rules! impl_binary_slice {
+    ($(#[$attr:meta])* $name:ident, $scalar:ident, $($bound:tt)+) => (
+        $(#[$attr])*
+        #[inline]
+        pub fn $name<T: $($bound)+>(x: &[T], y: &[T], hi: &mut [T], lo: &mut [T]) {
+            assert!(x.len() == y.len() && x.len() == hi.len() && x.len() == lo.len());
+            for (((x, y), h), l) in x.iter().zip(y.iter()).zip(hi.iter_mut()).zip(lo.iter_mut()) {
+                let (s, e) = $scalar(x.clone(), y.clone());
+                *h = s;
+                *l = e;
+            }
+        }
+    )
+}
+
+impl_binary_slice!(/// Applies `safetwosum_straight` elementwise.
+                   safetwosum_straight_slice, safetwosum_straight, FloatEFT);
+impl_binary_slice!(/// Applies `safetwosum_fma` elementwise.
+                   safetwosum_fma_slice, safetwosum_fma, FloatEFT + Fma);
+impl_binary_slice!(/// Applies `safetwoproduct_straight` elementwise.
+                   safetwoproduct_straight_slice, safetwoproduct_straight, FloatEFT);
+impl_binary_slice!(/// Applies `safetwoproduct_fma` elementwise.
+                   safetwoproduct_fma_slice, safetwoproduct_fma, FloatEFT + Fma);
+
+/// Applies `safesplit_straight` elementwise.
+#[inline]
+pub fn safesplit_straight_slice<T: FloatEFT>(a: &[T],
+                                             high: &mut [T],
+                                             low: &mut [T],
+                                             err: &mut [T]) {
+    assert!(a.len() == high.len() && a.len() == low.len() && a.len() == err.len());
+    for (((a, h), l), e) in a.iter().zip(high.iter_mut()).zip(low.iter_mut()).zip(err.iter_mut()) {
+        let (sh, sl, se) = safesplit_straight(a.clone());
+        *h = sh;
+        *l = sl;
+        *e = se;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use self::rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn same_as_scalar() {
+        let mut rng = rand::thread_rng();
+        let n = 1000;
+        let x: Vec<f64> = (0..n)
+            .map(|_| rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-500, 500)))
+            .collect();
+        let y: Vec<f64> = (0..n)
+            .map(|_| rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-500, 500)))
+            .collect();
+        let (mut hi, mut lo, mut err) = (vec![0.; n], vec![0.; n], vec![0.; n]);
+
+        safetwosum_straight_slice(&x, &y, &mut hi, &mut lo);
+        for i in 0..n {
+            assert_eq!((hi[i], lo[i]), safetwosum_straight(x[i], y[i]));
+        }
+        safetwosum_fma_slice(&x, &y, &mut hi, &mut lo);
+        for i in 0..n {
+            assert_eq!((hi[i], lo[i]), safetwosum_fma(x[i], y[i]));
+        }
+        safetwoproduct_straight_slice(&x, &y, &mut hi, &mut lo);
+        for i in 0..n {
+            assert_eq!((hi[i], lo[i]), safetwoproduct_straight(x[i], y[i]));
+        }
+        safetwoproduct_fma_slice(&x, &y, &mut hi, &mut lo);
+        for i in 0..n {
+            assert_eq!((hi[i], lo[i]), safetwoproduct_fma(x[i], y[i]));
+        }
+        safesplit_straight_slice(&x, &mut hi, &mut lo, &mut err);
+        for i in 0..n {
+            assert_eq!((hi[i], lo[i], err[i]), safesplit_straight(x[i]));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn length_mismatch() {
+        let (mut hi, mut lo) = ([0.; 2], [0.; 2]);
+        safetwosum_straight_slice(&[1., 2.], &[1.], &mut hi, &mut lo);
+    }
+}
@@ -7,18 +7,43 @@ pub fn split<T: FloatEFT>(a: T) -> (T, T) {
     (x.clone(), a - x)
 }
 
+/// Returns `(a_high, a_low, a_err)` which satisfy `a == 2 * a_high + 2 * a_low + a_err`,
+/// with the same guarantee as `safesplit_straight` for every finite `a`.
+///
+/// Up to 0.0.5 this returned the two parts `(a_high, a_low)` of `split(a)` with
+/// `a == a_high + a_low`. Both parts are now halved, so callers have to double them and
+/// add `a_err`, e.g. `a_high + (a_high + (2. * a_low + a_err))` as for `safesplit_straight`.
+///
+/// Deprecated because it is slower than `safesplit_straight`, which returns the same result.
+#[deprecated(since = "0.0.6",
+             note = "use `safesplit_straight`, which is faster and returns the same result")]
 #[inline]
-pub fn safesplit_branch<T: FloatEFT>(a: T) -> (T, T) {
-    // unsafe when usp(a) >= 2^997 (a >= 0x1.FFFFFF8000000p+1022) <-- ?????
-    // theoritically split(DOUBLE_MAX) == (succ(DOUBLE_MAX)==2^1023, some negative float)
-    if a > T::one() {
-        let t = split(a * T::epsilon());
-        (t.0 / T::epsilon(), t.1 / T::epsilon())
+pub fn safesplit_branch<T: FloatEFT>(a: T) -> (T, T, T) {
+    let half = T::one() / T::radix();
+    let aa = a.clone() * half;
+    let err = a - aa.clone() * T::radix(); // if usp(a) == 2^-1074, err == 2^-1074, else 0.
+    // scale aa into [min_positive / eps, 1] before split. all scalings are exact, and the
+    // high part of |aa| <= MAX / 2 rounds up to 2^emax at most.
+    let abs_aa = aa.clone().abs();
+    if abs_aa > T::one() {
+        let t = split(aa * T::epsilon());
+        (t.0 / T::epsilon(), t.1 / T::epsilon(), err)
+    } else if abs_aa < T::min_positive() / T::epsilon() {
+        let t = split(aa / T::epsilon());
+        (t.0 * T::epsilon(), t.1 * T::epsilon(), err)
     } else {
-        split(a)
+        let t = split(aa);
+        (t.0, t.1, err)
     }
 }
 
+/// Returns true if `split(a)` is correct, because `a * split_coef()` does not overflow.
+/// It may also be correct slightly above the threshold, where the product rounds to `MAX`.
+#[inline]
+pub fn split_is_safe<T: FloatEFT>(a: T) -> bool {
+    a.abs() < T::max_value() / T::split_coef()
+}
+
 #[inline]
 pub fn safesplit_straight<T: FloatEFT>(a: T) -> (T, T, T) {
     // Returns a_high, a_low, a_err which satisfy a == 2 * a_high + 2 * a_low + a_err.
@@ -26,7 +51,10 @@ pub fn safesplit_straight<T: FloatEFT>(a: T) -> (T, T, T) {
     let aa = a.clone() / T::radix();
     let err = a - aa.clone() * T::radix(); // if usp(a) == 2^-1074, err == 2^-1074, else 0.
 
-    let step = (((aa.clone() + T::min_positive()) - aa.clone()) / T::min_positive()) /
+    // naa + min_positive moves toward zero, so it does not round across a binade and
+    // (naa + min_positive) - naa is 0 or a power of two.
+    let naa = -aa.clone().abs();
+    let step = (((naa.clone() + T::min_positive()) - naa) / T::min_positive()) /
                (T::epsilon() * T::epsilon() * T::epsilon()) + T::epsilon();
     let split_shift = split(aa * step.clone());
 
@@ -38,11 +66,12 @@ mod tests {
     extern crate num_traits;
     extern crate rand;
 
-    use std::f64;
+    use std::{f32, f64};
     use self::rand::Rng;
     use self::num_traits::Float;
 
     use super::*;
+    use testutil::{sig_bits, NextFloat};
 
     #[allow(dead_code)]
     fn is_split(a: f64, b: f64) -> bool {
@@ -126,4 +155,122 @@ mod tests {
             assert!(s.0.abs() * 2f64.powi(-26) >= s.1.abs());
         }
     }
+
+    #[test]
+    fn f32_all_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            let fl = f32::from_bits(rng.gen::<u32>());
+            if !fl.is_finite() {
+                continue;
+            }
+            let s = safesplit_straight(fl);
+            assert_eq!(((s.2 + s.1 * 2.) + s.0) + s.0, fl);
+            assert!(sig_bits(s.0) <= 12 && sig_bits(s.1) <= 12);
+        }
+        // aa + min_positive rounds up to 2^-122
+        let s = safesplit_straight(3.540786e-37f32);
+        assert!(sig_bits(s.0) <= 12 && sig_bits(s.1) <= 12);
+    }
+
+    fn is_valid_split(a: f32, (h, l): (f32, f32)) -> bool {
+        h + l == a && sig_bits(h) <= 12 && sig_bits(l) <= 12
+    }
+
+    #[test]
+    fn is_safe() {
+        // every float from 2^-126 to MAX, and subnormals, of both signs
+        let mut bits = 0u32;
+        while bits < 0x7F80_0000 {
+            for &a in &[f32::from_bits(bits), -f32::from_bits(bits)] {
+                if split_is_safe(a) {
+                    assert!(is_valid_split(a, split(a)), "{:e}", a);
+                }
+            }
+            bits += if bits < 0x7000_0000 { 0x1_0001 } else { 1 };
+        }
+        let t = f32::MAX / <f32 as FloatEFT>::split_coef();
+        assert!(split_is_safe(t.next_below()) && !split_is_safe(t));
+        assert!(!split_is_safe(f32::INFINITY) && !split_is_safe(f32::NAN));
+    }
+
+    #[allow(deprecated)]
+    fn check_branch_f32(a: f32) {
+        let s = safesplit_branch(a);
+        assert_eq!(((s.2 + s.1 * 2.) + s.0) + s.0, a, "{:e}", a);
+        assert!(sig_bits(s.0) <= 12 && sig_bits(s.1) <= 12, "{:e}", a);
+        assert!(s.2 == 0. || s.2.abs() == f32::from_bits(1));
+        assert_eq!(s, safesplit_straight(a), "{:e}", a);
+    }
+
+    #[test]
+    fn branch_f32_boundary() {
+        // every subnormal number, the smallest normal binade and the largest binade
+        for bits in (0..0x0100_0000).chain(0x7F00_0000..0x7F80_0000) {
+            check_branch_f32(f32::from_bits(bits));
+            check_branch_f32(-f32::from_bits(bits));
+        }
+        // around the thresholds of the branches
+        for &c in &[1f32, 2., f32::MIN_POSITIVE / f32::EPSILON, 2. * f32::MIN_POSITIVE / f32::EPSILON] {
+            let mut a = c;
+            for _ in 0..100000 {
+                check_branch_f32(a);
+                check_branch_f32(-a);
+                a = a.next_below();
+            }
+        }
+    }
+
+    #[test]
+    fn branch_f32_all_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            let a = f32::from_bits(rng.gen::<u32>());
+            if a.is_finite() {
+                check_branch_f32(a);
+            }
+        }
+    }
+
+    #[allow(deprecated)]
+    fn check_branch_f64(a: f64) {
+        let s = safesplit_branch(a);
+        assert_eq!(((s.2 + s.1 * 2.) + s.0) + s.0, a, "{:e}", a);
+        // integer_decode of a subnormal number is not normalized
+        for &x in &[s.0, s.1] {
+            assert!(x.abs() < f64::MIN_POSITIVE || (x.integer_decode().0 & 0x7FFFFFF) == 0,
+                    "{:e}",
+                    a);
+        }
+        assert!(s.2 == 0. || s.2.abs() == f64::from_bits(1));
+        assert_eq!(s, safesplit_straight(a), "{:e}", a);
+    }
+
+    #[test]
+    fn branch_f64_boundary() {
+        let eta = f64::from_bits(1);
+        let mut a = f64::MAX;
+        let mut b = eta;
+        for _ in 0..1000000 {
+            check_branch_f64(a);
+            check_branch_f64(-a);
+            check_branch_f64(b);
+            check_branch_f64(-b);
+            a = a.next_below();
+            b = b.next_above();
+        }
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            // the whole subnormal range and the binades around the thresholds
+            let m = rng.gen_range::<u64>(0, 1 << 53) as f64;
+            check_branch_f64(m * eta);
+            check_branch_f64(m * 2f64.powi(-52 + rng.gen_range(-2, 2)));
+            check_branch_f64(m * 2f64.powi(-1022 + rng.gen_range(-2, 2)));
+            check_branch_f64(-m * 2f64.powi(1024 - 53 - rng.gen_range(0, 2)));
+        }
+        check_branch_f64(0.);
+        #[allow(deprecated)]
+        let s = safesplit_branch(f64::MAX);
+        assert_eq!(s.0, 2f64.powi(1023));
+    }
 }
@@ -0,0 +1,484 @@
+use traits::{FloatEFT, is_finite};
+use twosum::safetwosum_straight;
+
+/// Sum of the NaN and infinite elements of `p`, or `None` if all elements are finite.
+///
+/// The error terms of the EFTs are NaN for them, so the summations return this instead,
+/// as Python's `math.fsum`.
+#[inline]
+fn non_finite_sum<T: FloatEFT>(p: &[T]) -> Option<T> {
+    p.iter()
+        .filter(|x| !is_finite((*x).clone()))
+        .fold(None, |acc, x| Some(acc.map_or(x.clone(), |s| s + x.clone())))
+}
+
+/// Returns `(s, e)` where `s` is the recursive floating point sum of `p` and
+/// `e` approximates the accumulated rounding error, i.e. `s + e` is as accurate
+/// as if `p` were summed in twice the working precision (Sum2 of Ogita, Rump and Oishi[1]).
+///
+/// If some elements are NaN or infinite, returns their sum with a zero error, and if the
+/// recursive sum overflows, returns the infinity with a zero error.
+///
+/// 1. T. Ogita, S. M. Rump and S. Oishi, "Accurate Sum and Dot Product", SIAM J. Sci. Comput. 26(6), 1955-1988, 2005.
+#[inline]
+pub fn sum2_with_err<T: FloatEFT>(p: &[T]) -> (T, T) {
+    if let Some(s) = non_finite_sum(p) {
+        return (s, T::zero());
+    }
+    let mut s = T::zero();
+    let mut sigma = T::zero();
+    for x in p {
+        let (ss, q) = safetwosum_straight(s, x.clone());
+        if ss.is_infinite() {
+            // q is NaN, and the remaining elements are finite
+            return (ss, T::zero());
+        }
+        s = ss;
+        sigma = sigma + q;
+    }
+    (s, sigma)
+}
+
+#[inline]
+pub fn sum2<T: FloatEFT>(p: &[T]) -> T {
+    let (s, e) = sum2_with_err(p);
+    s + e
+}
+
+/// Returns the sum of `p` as accurate as if it were computed in `k`-fold working
+/// precision and then rounded (SumK of Ogita, Rump and Oishi). `k == 2` is equivalent to `sum2`.
+///
+/// If some elements are NaN or infinite, returns their sum, and if the recursive sum
+/// overflows, returns the infinity.
+///
+/// Panics if `k == 0`.
+pub fn sum_k<T: FloatEFT>(p: &[T], k: usize) -> T {
+    assert!(k >= 1);
+    if p.is_empty() {
+        return T::zero();
+    }
+    if let Some(s) = non_finite_sum(p) {
+        return s;
+    }
+    let mut q = p.to_vec();
+    for _ in 1..k {
+        vec_sum(&mut q);
+        if q[q.len() - 1].is_infinite() {
+            // the error terms are NaN
+            return q[q.len() - 1].clone();
+        }
+    }
+    let (last, rest) = q.split_last().unwrap();
+    rest.iter().fold(T::zero(), |acc, x| acc + x.clone()) + last.clone()
+}
+
+/// Error-free vector transformation: after the call, `p[n-1]` is the floating
+/// point sum of the original `p` and the other elements hold the rounding errors.
+#[inline]
+fn vec_sum<T: FloatEFT>(p: &mut [T]) {
+    for i in 1..p.len() {
+        let (s, e) = safetwosum_straight(p[i].clone(), p[i - 1].clone());
+        p[i] = s;
+        p[i - 1] = e;
+    }
+}
+
+/// Smallest power of two not less than `|a|` (Rump, Ogita and Oishi).
+/// `|a|` must not exceed `MAX * eps / 2`.
+#[inline]
+fn next_power_two<T: FloatEFT>(a: T) -> T {
+    let q = a.clone() / (T::epsilon() / T::radix());
+    let l = ((q.clone() + a.clone()) - q).abs();
+    if l == T::zero() { a.abs() } else { l }
+}
+
+/// Returns a faithfully rounded sum of `p` for arbitrary condition number
+/// (AccSum of Rump, Ogita and Oishi[2]).
+///
+/// If the extraction unit for the largest element would overflow, the leading
+/// extractions are carried out on `p / c` with a scale `c = eps^-2`, and their
+/// residuals are recovered exactly in the same way as `safesplit_straight`.
+///
+/// If some elements are NaN or infinite, returns their sum.
+///
+/// Panics if `p` is so long that `eps * (n + 2)^2 >= 1`.
+///
+/// 2. S. M. Rump, T. Ogita and S. Oishi, "Accurate Floating-Point Summation Part I: Faithful Rounding", SIAM J. Sci. Comput. 31(1), 189-224, 2008.
+pub fn acc_sum<T: FloatEFT>(p: &[T]) -> T {
+    let mut ms = T::one();
+    let mut m = 1;
+    while m < p.len() + 2 {
+        m *= 2;
+        ms = ms * T::radix();
+    }
+    let u = T::epsilon() / T::radix();
+    let phi = u.clone() * ms.clone();
+    let factor = T::epsilon() * ms.clone() * ms.clone();
+    assert!(factor < T::one());
+    let big = T::max_value() * u.clone() / (T::radix() * T::radix() * ms.clone());
+    if let Some(s) = non_finite_sum(p) {
+        return s;
+    }
+
+    let mut p = p.to_vec();
+    loop {
+        let mu = p.iter().fold(T::zero(), |acc, x| {
+            let x = x.clone().abs();
+            if x > acc { x } else { acc }
+        });
+        if mu == T::zero() {
+            return T::zero();
+        }
+        // every extraction unit sigma is held as sigma / c
+        let mut c = if mu > big { T::one() / (T::epsilon() * T::epsilon()) } else { T::one() };
+        let mut sigma = ms.clone() * next_power_two(mu / c.clone());
+        let mut t = T::zero();
+        loop {
+            if c != T::one() && sigma <= big.clone() / c.clone() {
+                sigma = sigma * c.clone();
+                t = t * c.clone();
+                c = T::one();
+            }
+            let tau = extract_vector(sigma.clone(), c.clone(), &mut p);
+            let (tau1, tau2) = safetwosum_straight(t.clone(), tau);
+            if tau1.clone().abs() >= factor.clone() * sigma.clone() ||
+               sigma <= T::min_positive() {
+                let rest = p.iter().fold(T::zero(), |acc, x| acc + x.clone());
+                let lo = tau2 * c.clone() + rest;
+                return if c == T::one() {
+                    tau1 + lo
+                } else {
+                    // tau1 * c may round up to 2^emax+1; lo / 2 is exact or negligible here
+                    (tau1 * (c / T::radix()) + lo / T::radix()) * T::radix()
+                };
+            }
+            t = tau1;
+            if t == T::zero() {
+                break;
+            }
+            sigma = phi.clone() * sigma;
+        }
+    }
+}
+
+/// Extracts the leading part of every `p[i]` with respect to `sigma * c` and
+/// returns the exact sum of the extracted parts divided by `c`.
+/// `p[i]` is overwritten by its exact remainder.
+#[inline]
+fn extract_vector<T: FloatEFT>(sigma: T, c: T, p: &mut [T]) -> T {
+    let mut tau = T::zero();
+    for x in p.iter_mut() {
+        let xs = x.clone() / c.clone();
+        let err = x.clone() - xs.clone() * c.clone(); // nonzero only if x / c is inexact
+        let q = (sigma.clone() + xs.clone()) - sigma.clone();
+        *x = (xs - q.clone()) * c.clone() + err;
+        tau = tau + q;
+    }
+    tau
+}
+
+/// Returns the sum of `p` correctly rounded to nearest (ties to even).
+///
+/// The exact sum is kept as a non-overlapping expansion in the manner of Shewchuk's
+/// grow-expansion and rounded at the end as in Python's `math.fsum`. If the partial
+/// sums may overflow, the inputs are divided by a power of two `c >= 4n`; the bits
+/// lost by the division are accumulated exactly in a second expansion, in the same
+/// way as `err_uf` of `safetwosum_straight`.
+///
+/// If some elements are NaN or infinite, returns their sum.
+pub fn sum_nearest<T: FloatEFT>(p: &[T]) -> T {
+    if let Some(s) = non_finite_sum(p) {
+        return s;
+    }
+    let mu = p.iter().fold(T::zero(), |acc, x| {
+        let x = x.clone().abs();
+        if x > acc { x } else { acc }
+    });
+    let mut c = T::one();
+    let mut m = 1;
+    while m < 4 * p.len() {
+        m *= 2;
+        c = c * T::radix();
+    }
+    if mu <= T::max_value() / c.clone() {
+        let mut partials = Vec::new();
+        for x in p {
+            grow_partials(&mut partials, x.clone());
+        }
+        return round_partials(&partials);
+    }
+
+    let (mut high, mut low) = (Vec::new(), Vec::new());
+    for x in p {
+        let xs = x.clone() / c.clone();
+        grow_partials(&mut low, x.clone() - xs.clone() * c.clone());
+        grow_partials(&mut high, xs);
+    }
+    // move the subnormal part of `high` to `low` so that a sticky bit fits below `high`
+    let n_small = high.iter().take_while(|x| (*x).clone().abs() < T::min_positive()).count();
+    for x in high.drain(..n_small) {
+        grow_partials(&mut low, x * c.clone());
+    }
+    match high.last().cloned() {
+        None => round_partials(&low),
+        Some(ref top) if top.clone().abs() < T::max_value() / (T::radix() * c.clone()) => {
+            for x in high {
+                grow_partials(&mut low, x * c.clone());
+            }
+            round_partials(&low)
+        }
+        Some(_) => {
+            if let Some(l) = low.last() {
+                let sticky = if *l > T::zero() {
+                    T::unit_underflow()
+                } else {
+                    -T::unit_underflow()
+                };
+                high.insert(0, sticky);
+            }
+            round_partials(&high) * c
+        }
+    }
+}
+
+/// Adds `x` to the non-overlapping expansion `partials` (in increasing order of
+/// magnitude) exactly, eliminating zero components.
+#[inline]
+pub(crate) fn grow_partials<T: FloatEFT>(partials: &mut Vec<T>, x: T) {
+    let mut x = x;
+    let mut i = 0;
+    for j in 0..partials.len() {
+        let (hi, lo) = safetwosum_straight(x, partials[j].clone());
+        if lo != T::zero() {
+            partials[i] = lo;
+            i += 1;
+        }
+        x = hi;
+    }
+    partials.truncate(i);
+    if x != T::zero() {
+        partials.push(x);
+    }
+}
+
+/// Rounds the exact value of a non-overlapping expansion to nearest.
+#[inline]
+pub(crate) fn round_partials<T: FloatEFT>(partials: &[T]) -> T {
+    let mut n = partials.len();
+    if n == 0 {
+        return T::zero();
+    }
+    n -= 1;
+    let mut hi = partials[n].clone();
+    let mut lo = T::zero();
+    while n > 0 {
+        n -= 1;
+        let (x, y) = (hi.clone(), partials[n].clone());
+        hi = x.clone() + y.clone();
+        lo = y - (hi.clone() - x);
+        if lo != T::zero() {
+            break;
+        }
+    }
+    // a tie which was broken by the discarded tail
+    if n > 0 &&
+       ((lo < T::zero() && partials[n - 1] < T::zero()) ||
+        (lo > T::zero() && partials[n - 1] > T::zero())) {
+        let y = lo * T::radix();
+        let x = hi.clone() + y.clone();
+        if y == x.clone() - hi.clone() {
+            hi = x;
+        }
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn normal() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000 {
+            let p: Vec<f32> = (0..100)
+                .map(|_| rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-10, 10)))
+                .collect();
+            // every partial sum is exact in f64
+            let exact = p.iter().fold(0f64, |acc, &x| acc + x as f64);
+            let abs_sum = p.iter().fold(0f64, |acc, &x| acc + (x as f64).abs());
+            let gamma = 100. * 2f64.powi(-24);
+            let res = sum2(&p) as f64;
+            assert!((res - exact).abs() <= 2f64.powi(-24) * exact.abs() + gamma * gamma * abs_sum);
+        }
+    }
+
+    #[test]
+    fn ill_conditioned() {
+        assert_eq!(sum2(&[1e16, 1., -1e16]), 1.);
+        assert_eq!(sum2(&[1., 1e100, 1., -1e100]), 2.);
+        assert_eq!(sum2::<f64>(&[]), 0.);
+    }
+
+    #[test]
+    fn non_finite() {
+        let (inf, nan) = (f64::INFINITY, f64::NAN);
+        for p in &[vec![inf, 1.], vec![1., -inf], vec![f64::MAX, f64::MAX, -inf]] {
+            let s = p.iter().filter(|x| x.is_infinite()).sum::<f64>();
+            assert_eq!(sum2(p), s);
+            assert_eq!(sum_k(p, 3), s);
+            assert_eq!(acc_sum(p), s);
+            assert_eq!(sum_nearest(p), s);
+        }
+        for p in &[vec![inf, 1., -inf], vec![nan], vec![1., nan, inf]] {
+            assert!(sum2(p).is_nan());
+            assert!(sum_k(p, 3).is_nan());
+            assert!(acc_sum(p).is_nan());
+            assert!(sum_nearest(p).is_nan());
+        }
+        assert_eq!(acc_sum(&[inf]), inf);
+        assert_eq!(acc_sum(&[-inf, -1.]), -inf);
+    }
+
+    #[test]
+    fn overflow() {
+        let (max, inf) = (f64::MAX, f64::INFINITY);
+        assert_eq!(sum2(&[max, max]), inf);
+        assert_eq!(sum2(&[max, max, -max]), inf);
+        assert_eq!(sum2(&[-max, 1., -max]), -inf);
+        assert_eq!(sum2_with_err(&[max, max]), (inf, 0.));
+        assert_eq!(sum2(&[max, -max, max]), max);
+        assert_eq!(sum_k(&[max, max], 3), inf);
+        assert_eq!(sum_k(&[max, max, -max], 3), inf);
+        assert_eq!(sum_k(&[-max, -max], 1), -inf);
+        assert_eq!(sum_k(&[max, -max, max], 3), max);
+    }
+
+    #[test]
+    fn k_fold() {
+        let p = [2f64.powi(100), 1., -2f64.powi(100), 2f64.powi(-60), -1.];
+        assert_eq!(sum_k(&p, 1), -1.);
+        assert_eq!(sum_k(&p, 2), 0.);
+        assert_eq!(sum_k(&p, 2), sum2(&p));
+        assert_eq!(sum_k(&p, 3), 2f64.powi(-60));
+        assert_eq!(sum_k::<f64>(&[], 4), 0.);
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        assert_eq!(sum_k(&[f64::MAX, eta, -f64::MAX, 2. * eta], 3), 3. * eta);
+    }
+
+    fn is_faithful(p: &[f32], res: f32) -> bool {
+        // all test inputs are multiples of 2^-60 and less than 2^40
+        let to_int = |x: f32| (x as f64 * 2f64.powi(60)) as i128;
+        let exact = p.iter().fold(0i128, |acc, &x| acc + to_int(x));
+        let (pred, succ) = if res == 0. {
+            (-f32::from_bits(1), f32::from_bits(1))
+        } else if res > 0. {
+            (f32::from_bits(res.to_bits() - 1), f32::from_bits(res.to_bits() + 1))
+        } else {
+            (f32::from_bits(res.to_bits() + 1), f32::from_bits(res.to_bits() - 1))
+        };
+        exact == to_int(res) || (to_int(pred) < exact && exact < to_int(succ))
+    }
+
+    fn is_nearest(p: &[f32], res: f32) -> bool {
+        let to_int = |x: f32| (x as f64 * 2f64.powi(60)) as i128;
+        let exact = p.iter().fold(0i128, |acc, &x| acc + to_int(x));
+        if res == 0. {
+            return exact.abs() <= to_int(f32::from_bits(1)) / 2;
+        }
+        let (pred, succ) = (f32::from_bits(res.to_bits() - 1), f32::from_bits(res.to_bits() + 1));
+        let (d, dp, ds) = ((exact - to_int(res)).abs(),
+                           (exact - to_int(pred)).abs(),
+                           (exact - to_int(succ)).abs());
+        let even = res.to_bits() & 1 == 0;
+        (d < dp || (d == dp && even)) && (d < ds || (d == ds && even))
+    }
+
+    #[test]
+    fn faithful() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000 {
+            let mut p: Vec<f32> = (0..50)
+                .map(|_| rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-14, 14)))
+                .collect();
+            let neg: Vec<f32> = p.iter().map(|&x| -x).collect();
+            p.extend(neg);
+            for _ in 0..3 {
+                p.push(rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-30, 14)));
+            }
+            rng.shuffle(&mut p);
+            let res = acc_sum(&p);
+            assert!(is_faithful(&p, res));
+            assert!(is_faithful(&p[..60], acc_sum(&p[..60])));
+            // large enough to extract on scaled values
+            let scaled: Vec<f32> = p.iter().map(|&x| x * 2f32.powi(100)).collect();
+            assert!(is_faithful(&p, acc_sum(&scaled) * 2f32.powi(-100)));
+        }
+    }
+
+    #[test]
+    fn nearest() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000 {
+            let mut p: Vec<f32> = (0..50)
+                .map(|_| rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-14, 14)))
+                .collect();
+            let neg: Vec<f32> = p.iter().map(|&x| -x).collect();
+            p.extend(neg);
+            for _ in 0..3 {
+                p.push(rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-30, 14)));
+            }
+            rng.shuffle(&mut p);
+            assert!(is_nearest(&p, sum_nearest(&p)));
+            assert!(is_nearest(&p[..60], sum_nearest(&p[..60])));
+            // large enough to take the scaled path
+            let scaled: Vec<f32> = p.iter().map(|&x| x * 2f32.powi(112)).collect();
+            assert!(is_nearest(&p, sum_nearest(&scaled) * 2f32.powi(-112)));
+        }
+        // ties to even
+        assert_eq!(sum_nearest(&[2f64.powi(53), 1.]), 2f64.powi(53));
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        assert_eq!(sum_nearest(&[2f64.powi(53), 1., eta]), 2f64.powi(53) + 2.);
+        assert_eq!(sum_nearest(&[2f64.powi(53), 3., -eta]), 2f64.powi(53) + 2.);
+        assert_eq!(sum_nearest::<f64>(&[]), 0.);
+    }
+
+    #[test]
+    fn nearest_large() {
+        let m = f64::MAX;
+        let tiny = 2f64.powi(-1022) * 2f64.powi(-52);
+        assert_eq!(sum_nearest(&[m, m, -m, -m, tiny]), tiny);
+        assert_eq!(sum_nearest(&[m, tiny, m, -m, -m, tiny]), 2. * tiny);
+        assert_eq!(sum_nearest(&[m, m, -m]), m);
+        assert_eq!(sum_nearest(&[-m, -m, m, -2f64.powi(969)]), -m);
+        assert_eq!(sum_nearest(&[m, 2f64.powi(970), -tiny]), m);
+        assert_eq!(sum_nearest(&[m, 2f64.powi(970)]), f64::INFINITY);
+        assert_eq!(sum_nearest(&[m, 2f64.powi(970), tiny]), f64::INFINITY);
+        let t = 2f64.powi(14) * tiny;
+        assert_eq!(sum_nearest(&[m, m, t, -m, -m, t]), 2. * t);
+    }
+
+    #[test]
+    fn faithful_large() {
+        assert_eq!(acc_sum(&[f64::MAX, f64::MAX, -f64::MAX, 1., -f64::MAX]), 1.);
+        let eta = 2f64.powi(-1022) * 2f64.powi(-52);
+        assert_eq!(acc_sum(&[f64::MAX, eta, -f64::MAX * 0.5, -f64::MAX * 0.5]), eta);
+        assert_eq!(acc_sum(&[f64::MAX, 2f64.powi(969), -2f64.powi(969)]), f64::MAX);
+        assert_eq!(acc_sum(&[-f64::MAX, -f64::MAX * 0.5, f64::MAX]), -f64::MAX * 0.5);
+        assert_eq!(acc_sum(&[eta, eta, -2. * eta]), 0.);
+        assert_eq!(acc_sum::<f64>(&[]), 0.);
+    }
+
+    #[test]
+    fn large() {
+        let res = sum2(&[f64::MAX, -f64::MAX, 1., f64::MAX * 0.5, -f64::MAX * 0.5]);
+        assert_eq!(res, 1.);
+        let (s, e) = sum2_with_err(&[f64::MAX, 2f64.powi(969), -f64::MAX]);
+        assert_eq!(s + e, 2f64.powi(969));
+    }
+}
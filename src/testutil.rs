@@ -0,0 +1,64 @@
+//! Helpers shared by the unit tests.
+
+extern crate rand;
+
+use std::f32;
+use self::rand::Rng;
+
+/// Random bit pattern, where NaN and infinities are replaced by zero.
+pub fn gen_f32(rng: &mut rand::ThreadRng) -> f32 {
+    let x = f32::from_bits(rng.gen::<u32>());
+    if x.is_finite() { x } else { 0. }
+}
+
+/// Value of the lowest set bit of `a`, which must not be zero.
+pub fn lowest_bit(a: f32) -> f64 {
+    let bits = a.to_bits();
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = (bits & 0x7fffff) | if exp == 0 { 0 } else { 1 << 23 };
+    2f64.powi(if exp == 0 { -149 } else { exp - 150 } + mant.trailing_zeros() as i32)
+}
+
+/// Number of significant bits of the mantissa.
+pub fn sig_bits(x: f32) -> u32 {
+    let bits = x.to_bits();
+    let m = if bits & 0x7F80_0000 == 0 {
+        bits & 0x7F_FFFF
+    } else {
+        (bits & 0x7F_FFFF) | 0x80_0000
+    };
+    if m == 0 { 0 } else { 32 - m.leading_zeros() - m.trailing_zeros() }
+}
+
+/// Adjacent floating point numbers by the bit pattern, independent of `succ` and `pred`.
+/// NaN and the infinity in the direction are returned unchanged.
+pub trait NextFloat: Sized {
+    fn next_above(self) -> Self;
+
+    fn next_below(self) -> Self;
+}
+
+macro_rules! impl_next_float {
+    ($t:ident) => (
+        impl NextFloat for $t {
+            fn next_above(self) -> $t {
+                if self.is_nan() || (self.is_infinite() && self > 0.) {
+                    self
+                } else if self == 0. {
+                    $t::from_bits(1)
+                } else if self > 0. {
+                    $t::from_bits(self.to_bits() + 1)
+                } else {
+                    $t::from_bits(self.to_bits() - 1)
+                }
+            }
+
+            fn next_below(self) -> $t {
+                -(-self).next_above()
+            }
+        }
+    )
+}
+
+impl_next_float!(f32);
+impl_next_float!(f64);
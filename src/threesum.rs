@@ -0,0 +1,131 @@
+use traits::FloatEFT;
+use twosum::safetwosum_straight;
+
+/// Returns `(s, e1, e2)` where `s + e1 + e2 == a + b + c` exactly and the three are
+/// non-overlapping, in decreasing order of magnitude with zeros moved to the tail.
+///
+/// This is `renormalize` of three numbers. `s` is not always the nearest rounding of
+/// `a + b + c`, but it is zero only if the sum is.
+#[inline]
+pub fn threesum<T: FloatEFT>(a: T, b: T, c: T) -> (T, T, T) {
+    let (t, u) = safetwosum_straight(b, c);
+    let (v, e2) = safetwosum_straight(a, u);
+    let (s, e1) = safetwosum_straight(v, t);
+    let mut x = [s, e1, e2];
+    zeros_to_tail(&mut x);
+    let [s, e1, e2] = x;
+    (s, e1, e2)
+}
+
+/// Moves the zeros of `x` to the tail, keeping the order of the other components.
+#[inline]
+fn zeros_to_tail<T: FloatEFT>(x: &mut [T]) {
+    let mut k = 0;
+    for i in 0..x.len() {
+        if x[i] != T::zero() {
+            x.swap(k, i);
+            k += 1;
+        }
+    }
+}
+
+/// Rearranges `x` into a non-overlapping expansion with the same exact sum: for every
+/// nonzero `x[i]` and `x[j]` with `i < j`, the lowest nonzero bit of `x[i]` is higher than
+/// the highest bit of `x[j]`. Zeros are moved to the tail, so `x[0]` is zero only if the
+/// sum is, and otherwise differs from the sum by less than its lowest nonzero bit.
+///
+/// This is Shewchuk's grow-expansion[1] applied to `x[n-1], ..., x[0]` in turn. It takes
+/// `n(n-1)/2` of `safetwosum_straight`, so it is meant for a few components, e.g.
+/// triple- or quad-word numbers. The result is correct unless the sum
+/// of some tail `x[i..]` overflows.
+///
+/// 1. J. R. Shewchuk, "Adaptive Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates", Discrete Comput. Geom. 18(3), 305-363, 1997.
+pub fn renormalize<T: FloatEFT>(x: &mut [T]) {
+    let n = x.len();
+    for i in (0..n).rev() {
+        // x[i+1..] is an expansion in decreasing order. grow it from the bottom by x[i].
+        let mut q = x[i].clone();
+        for j in (i + 1..n).rev() {
+            let (s, e) = safetwosum_straight(q, x[j].clone());
+            x[j] = e;
+            q = s;
+        }
+        x[i] = q;
+    }
+    zeros_to_tail(x);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use sum::sum_nearest;
+    use testutil::{lowest_bit, NextFloat};
+
+    // random exponent over the finite range, unlike the bit patterns of testutil::gen_f32
+    fn gen_exact_f32(rng: &mut rand::ThreadRng) -> f32 {
+        rng.gen_range::<f32>(-1., 1.) * 2f32.powi(rng.gen_range(-149, 126))
+    }
+
+    fn check(input: &[f32], output: &[f32]) {
+        // f32 numbers are exact in f64, and so is the sign of their sum
+        let mut diff: Vec<f64> = input.iter().map(|&a| a as f64).collect();
+        diff.extend(output.iter().map(|&a| -a as f64));
+        assert_eq!(sum_nearest(&diff), 0., "{:?} -> {:?}", input, output);
+        let nonzero: Vec<f32> = output.iter().cloned().filter(|&a| a != 0.).collect();
+        assert_eq!(&output[..nonzero.len()], &nonzero[..], "{:?} -> {:?}", input, output);
+        for w in nonzero.windows(2) {
+            assert!((w[1].abs() as f64) < lowest_bit(w[0]), "{:?} -> {:?}", input, output);
+        }
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300000 {
+            let (a, b, c) = (gen_exact_f32(&mut rng), gen_exact_f32(&mut rng), gen_exact_f32(&mut rng));
+            let (s, e1, e2) = threesum(a, b, c);
+            check(&[a, b, c], &[s, e1, e2]);
+            let (s, e1, e2) = threesum(a, -(a + b), b);
+            check(&[a, -(a + b), b], &[s, e1, e2]);
+
+            let n = rng.gen_range(0, 8);
+            let x: Vec<f32> = (0..n).map(|_| gen_exact_f32(&mut rng)).collect();
+            let mut y = x.clone();
+            renormalize(&mut y);
+            check(&x, &y);
+            if let Some(&top) = y.first() {
+                let exact = sum_nearest(&x.iter().map(|&a| a as f64).collect::<Vec<_>>());
+                if top == 0. {
+                    assert_eq!(exact, 0.);
+                } else {
+                    assert!((top as f64 - exact).abs() < lowest_bit(top));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extreme() {
+        let (max, eta) = (f64::MAX, 2f64.powi(-1022) * 2f64.powi(-52));
+        let ulp = max - max.next_below();
+        assert_eq!(threesum(max, ulp / 8., ulp / 8.), (max, ulp / 4., 0.));
+        assert_eq!(threesum(max, -max, eta), (eta, 0., 0.));
+        assert_eq!(threesum(eta, 1., -1.), (eta, 0., 0.));
+        assert_eq!(threesum(eta, max, -eta), (max, 0., 0.));
+        let mut x = [1., max.next_below(), ulp / 2., eta];
+        renormalize(&mut x);
+        assert_eq!(x, [max.next_below(), ulp / 2., 1., eta]);
+        let mut x = [3. * eta, -eta, 2f64.powi(-1022), -2f64.powi(-1022)];
+        renormalize(&mut x);
+        assert_eq!(x, [2. * eta, 0., 0., 0.]);
+        let mut x = [max, 1., -max, 0.];
+        renormalize(&mut x);
+        assert_eq!(x, [1., 0., 0., 0.]);
+        renormalize::<f64>(&mut []);
+    }
+}
@@ -4,6 +4,54 @@ use core::clone::Clone;
 use self::num_traits::One;
 use self::float_traits::{IEEE754Float, BinaryFloat};
 
+/// Fused multiply-add `a * b + c` with a single rounding.
+///
+/// Implemented with `mul_add` of the standard library, so it works on stable.
+/// Without the `fma` target-feature it falls back to (slow) software emulation.
+pub trait Fma {
+    fn fma(a: Self, b: Self, c: Self) -> Self;
+}
+
+impl Fma for f64 {
+    #[inline]
+    fn fma(a: f64, b: f64, c: f64) -> f64 {
+        a.mul_add(b, c)
+    }
+}
+
+impl Fma for f32 {
+    #[inline]
+    fn fma(a: f32, b: f32, c: f32) -> f32 {
+        a.mul_add(b, c)
+    }
+}
+
+/// Bound of the algorithms that use `safetwoproduct_fma` with `use-fma` feature and
+/// `safetwoproduct_straight` otherwise: the same as `Fma` with the feature, and implemented
+/// for every type without it.
+#[cfg(feature = "use-fma")]
+pub trait SelectedFma: Fma {}
+
+#[cfg(feature = "use-fma")]
+impl<T: Fma> SelectedFma for T {}
+
+#[cfg(not(feature = "use-fma"))]
+pub trait SelectedFma {}
+
+#[cfg(not(feature = "use-fma"))]
+impl<T> SelectedFma for T {}
+
+#[inline]
+pub fn fma<T: Fma>(a: T, b: T, c: T) -> T {
+    Fma::fma(a, b, c)
+}
+
+/// `false` for NaN and infinities, without the methods of `Float`.
+#[inline]
+pub fn is_finite<T: FloatEFT>(a: T) -> bool {
+    a.clone() - a == T::zero()
+}
+
 pub trait FloatEFT: IEEE754Float + Clone {
     #[inline]
     fn split_coef() -> Self {
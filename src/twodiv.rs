@@ -0,0 +1,82 @@
+use traits::FloatEFT;
+use twoprod::safetwoproduct_straight;
+use traits::{fma, Fma};
+
+#[inline]
+pub fn safetwodiv<T: FloatEFT>(a: T, b: T) -> (T, T) {
+    // Returns q == a / b and r == a - q * b.
+    let q = a.clone() / b.clone();
+    if a.clone().abs() >= T::one() / (T::min_positive() / T::epsilon()) {
+        // q * b may round up to overflow, so compute eps * r instead. all scalings are exact.
+        let (aa, qq) = (a * T::epsilon(), q.clone() * T::epsilon());
+        let (p, e) = safetwoproduct_straight(qq, b);
+        (q, ((aa - p) - e) / T::epsilon())
+    } else {
+        let (p, e) = safetwoproduct_straight(q.clone(), b);
+        (q, (a - p) - e) // a - p is exact
+    }
+}
+
+#[inline]
+pub fn safetwodiv_fma<T: FloatEFT + Fma>(a: T, b: T) -> (T, T) {
+    let q = a.clone() / b.clone();
+    (q.clone(), fma(-q, b, a))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+
+    fn check(a: f64, b: f64) {
+        let (q, r) = safetwodiv(a, b);
+        assert_eq!(q, a / b);
+        assert_eq!(r, (-q).mul_add(b, a));
+
+        #[cfg(feature = "use-fma")]
+        {
+            assert_eq!(safetwodiv_fma(a, b), (q, r));
+        }
+    }
+
+    #[test]
+    fn normal() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            check(rng.gen_range::<f64>(2f64.powi(-500), 2f64.powi(500)) *
+                  rng.choose(&[1., -1.]).unwrap(),
+                  rng.gen_range::<f64>(2f64.powi(-500), 2f64.powi(500)) *
+                  rng.choose(&[1., -1.]).unwrap());
+        }
+    }
+
+    #[test]
+    fn large() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            check(rng.gen_range::<f64>(2f64.powi(1020), f64::MAX) *
+                  rng.choose(&[1., -1.]).unwrap(),
+                  rng.gen_range::<f64>(1., 2f64.powi(1020)) * rng.choose(&[1., -1.]).unwrap());
+        }
+        check(f64::MAX, 3.);
+        check(f64::MAX, 1. + f64::EPSILON);
+        check(-f64::MAX, 1. + 3. * f64::EPSILON);
+        check(f64::MAX, f64::MAX);
+    }
+
+    #[test]
+    fn subnormal() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            // the remainder is a multiple of 2^-1074, hence representable, if b is an integer
+            check((rng.gen_range::<i64>(-0x1F_FFFF_FFFF_FFFF, 0x20_0000_0000_0000) as f64) *
+                  2f64.powi(-1022) * 2f64.powi(-52),
+                  rng.gen_range::<i64>(1, 0x20_0000_0000_0000) as f64 *
+                  rng.choose(&[1., -1.]).unwrap());
+        }
+    }
+}
@@ -1,8 +1,7 @@
 use traits::FloatEFT;
 use twosum::fasttwosum;
-use split::{split, safesplit_straight};
-#[cfg(any(feature = "use-fma", feature = "doc"))]
-use fma::{fma, Fma};
+use split::{split, split_is_safe, safesplit_straight};
+use traits::{fma, Fma, SelectedFma};
 
 #[inline]
 pub fn twoproduct<T: FloatEFT>(a: T, b: T) -> (T, T) {
@@ -12,6 +11,22 @@ pub fn twoproduct<T: FloatEFT>(a: T, b: T) -> (T, T) {
      a2.clone() * b2.clone() - (((prod - a1.clone() * b1.clone()) - a1 * b2) - a2 * b1))
 }
 
+/// Returns true if `twoproduct(a, b)` is correct: both factors can be split, and
+/// `4 * MIN_POSITIVE / EPSILON <= |a * b| <= MAX / 2`, or `a * b` is exactly zero. Above
+/// the range `a1 * b1` may overflow, and below it the error may underflow, so this is
+/// sufficient but not necessary.
+#[inline]
+pub fn twoproduct_is_exact<T: FloatEFT>(a: T, b: T) -> bool {
+    let p = (a.clone() * b.clone()).abs();
+    let in_range = if p == T::zero() {
+        a == T::zero() || b == T::zero()
+    } else {
+        p >= T::radix() * T::radix() * T::min_positive() / T::epsilon() &&
+        p <= T::max_value() / T::radix()
+    };
+    in_range && split_is_safe(a) && split_is_safe(b)
+}
+
 #[inline]
 pub fn safetwoproduct_branch<T: FloatEFT>(a: T, b: T) -> (T, T) {
     let prod = a.clone() * b.clone();
@@ -44,13 +59,26 @@ pub fn safetwoproduct_straight<T: FloatEFT>(a: T, b: T) -> (T, T) {
        (T::radix() * T::radix() * a2) * b1)) + a * b3 + b * a3)
 }
 
-#[cfg(any(feature = "use-fma", feature = "doc"))]
 #[inline]
 pub fn safetwoproduct_fma<T: FloatEFT + Fma>(a: T, b: T) -> (T, T) {
     let prod = a.clone() * b.clone();
     (prod.clone(), fma(a, b, -prod))
 }
 
+/// `safetwoproduct_fma` with `use-fma` feature, `safetwoproduct_straight` otherwise.
+#[cfg(feature = "use-fma")]
+#[inline]
+pub(crate) fn safetwoproduct_selected<T: FloatEFT + SelectedFma>(a: T, b: T) -> (T, T) {
+    safetwoproduct_fma(a, b)
+}
+
+/// `safetwoproduct_fma` with `use-fma` feature, `safetwoproduct_straight` otherwise.
+#[cfg(not(feature = "use-fma"))]
+#[inline]
+pub(crate) fn safetwoproduct_selected<T: FloatEFT + SelectedFma>(a: T, b: T) -> (T, T) {
+    safetwoproduct_straight(a, b)
+}
+
 #[cfg(test)]
 mod tests {
     extern crate rand;
@@ -100,6 +128,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn is_exact() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000000 {
+            let gen = |rng: &mut rand::ThreadRng| {
+                rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-1074, 1024))
+            };
+            let (l, r) = (gen(&mut rng), gen(&mut rng));
+            if twoproduct_is_exact(l, r) {
+                assert_eq!(twoproduct(l, r), (l * r, l.mul_add(r, -l * r)), "{:e} {:e}", l, r);
+            }
+        }
+        let tiny = 4. * f64::MIN_POSITIVE / f64::EPSILON;
+        assert!(twoproduct_is_exact(tiny, 1. + f64::EPSILON));
+        assert!(!twoproduct_is_exact(tiny / 2., 1. + f64::EPSILON));
+        assert!(twoproduct_is_exact(0., f64::from_bits(1)) && !twoproduct_is_exact(0., f64::MAX));
+        assert!(!twoproduct_is_exact(f64::MAX, 0.5) && !twoproduct_is_exact(2f64.powi(1000), 0.1));
+        assert!(!twoproduct_is_exact(2f64.powi(-600), 2f64.powi(-600)));
+    }
+
     #[test]
     fn corner_case() {
         let (l, r) = (6.929001713869936e+236, 2.5944475251952003e+71);
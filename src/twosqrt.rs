@@ -0,0 +1,76 @@
+use traits::FloatEFT;
+use twoprod::safetwoproduct_straight;
+use traits::{fma, Fma};
+
+#[inline]
+pub fn safetwosqrt<T: FloatEFT>(a: T) -> (T, T) {
+    // Returns s == sqrt(a) and r == a - s * s.
+    let s = a.clone().sqrt();
+    if a >= T::one() / (T::min_positive() / T::epsilon()) {
+        // s * s may round up to overflow, so compute eps^2 * r instead. all scalings are exact.
+        let eps2 = T::epsilon() * T::epsilon();
+        let (aa, ss) = (a * eps2.clone(), s.clone() * T::epsilon());
+        let (p, e) = safetwoproduct_straight(ss.clone(), ss);
+        (s, ((aa - p) - e) / eps2)
+    } else {
+        let (p, e) = safetwoproduct_straight(s.clone(), s.clone());
+        (s, (a - p) - e) // a - p is exact
+    }
+}
+
+#[inline]
+pub fn safetwosqrt_fma<T: FloatEFT + Fma>(a: T) -> (T, T) {
+    let s = a.clone().sqrt();
+    (s.clone(), fma(-s.clone(), s, a))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::f64;
+    use self::rand::Rng;
+
+    use super::*;
+
+    fn check(a: f64) {
+        let (s, r) = safetwosqrt(a);
+        assert_eq!(s, a.sqrt());
+        assert_eq!(r, (-s).mul_add(s, a));
+
+        #[cfg(feature = "use-fma")]
+        {
+            assert_eq!(safetwosqrt_fma(a), (s, r));
+        }
+    }
+
+    #[test]
+    fn normal() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            check(rng.gen_range::<f64>(2f64.powi(-960), 2f64.powi(960)));
+        }
+        check(0.);
+        check(1.);
+        check(2.);
+    }
+
+    #[test]
+    fn large() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            check(rng.gen_range::<f64>(2f64.powi(960), f64::MAX));
+        }
+        check(f64::MAX);
+        check(f64::MAX * (1. - f64::EPSILON));
+    }
+
+    #[test]
+    fn small() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000000 {
+            // the remainder is a multiple of ulp(s)^2, which is representable above 2^-968
+            check(rng.gen_range::<f64>(2f64.powi(-968), 2f64.powi(-960)));
+        }
+    }
+}
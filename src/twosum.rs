@@ -1,6 +1,5 @@
 use traits::FloatEFT;
-#[cfg(any(feature = "use-fma", feature = "doc"))]
-use fma::{fma, Fma};
+use traits::{fma, Fma};
 
 #[inline]
 pub fn fasttwosum<T: FloatEFT>(x: T, y: T) -> (T, T) {
@@ -15,6 +14,21 @@ pub fn twosum<T: FloatEFT>(x: T, y: T) -> (T, T) {
     (sum.clone(), (x - (sum.clone() - tmp.clone())) + (y - tmp))
 }
 
+/// Returns true if `fasttwosum(x, y)` is correct: the sum does not overflow and
+/// `|x| >= |y|`, which makes `sum - x` exact.
+#[inline]
+pub fn fasttwosum_is_exact<T: FloatEFT>(x: T, y: T) -> bool {
+    (x.clone() + y.clone()).abs() <= T::max_value() && x.abs() >= y.abs()
+}
+
+/// Returns true if `twosum(x, y)` is correct: the sum does not overflow and
+/// `|y| <= MAX / 2`. Otherwise `sum - x`, which is close to `y`, may overflow (e.g.
+/// `twosum(3.563062444487454e307, -MAX)`), so this is sufficient but not necessary.
+#[inline]
+pub fn twosum_is_exact<T: FloatEFT>(x: T, y: T) -> bool {
+    (x + y.clone()).abs() <= T::max_value() && y.abs() <= T::max_value() / T::radix()
+}
+
 #[inline]
 pub fn safetwosum_branch<T: FloatEFT>(x: T, y: T) -> (T, T) {
     if x.clone().abs() > y.clone().abs() {
@@ -36,7 +50,6 @@ pub fn safetwosum_straight<T: FloatEFT>(x: T, y: T) -> (T, T) {
     (s, (T::radix() * ee + err_h) + err_uf)
 }
 
-#[cfg(any(feature = "use-fma", feature = "doc"))]
 #[inline]
 pub fn safetwosum_fma<T: FloatEFT + Fma>(x: T, y: T) -> (T, T) {
     let s = x.clone() + y.clone();
@@ -113,6 +126,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn is_exact() {
+        let mut rng = rand::thread_rng();
+        let max = f64::MAX;
+        for _ in 0..1000000 {
+            let (l, r) = (rng.gen_range::<f64>(-1., 1.) * max, rng.gen_range::<f64>(-1., 1.) * max);
+            let r = if rng.gen() { r } else { r * 2f64.powi(-rng.gen_range(0, 1100)) };
+            let expected = safetwosum_straight(l, r);
+            if twosum_is_exact(l, r) {
+                assert_eq!(twosum(l, r), expected);
+            }
+            if fasttwosum_is_exact(l, r) {
+                assert_eq!(fasttwosum(l, r), expected);
+            }
+            assert_eq!(fasttwosum_is_exact(l, r) || fasttwosum_is_exact(r, l),
+                       !expected.0.is_infinite());
+        }
+        assert!(!twosum_is_exact(3.563062444487454e307, -max));
+        assert!(twosum_is_exact(-max, 3.563062444487454e307));
+        assert!(twosum_is_exact(max, -max / 2.));
+        assert!(!twosum_is_exact(max, max / 2.) && !twosum_is_exact(f64::NAN, 0.));
+        assert!(!fasttwosum_is_exact(1., 2.) && fasttwosum_is_exact(-max, max));
+    }
+
     #[test]
     fn corner_case() {
         let res1 = safetwosum_straight(3.5630624444874539e+307, -1.7976931348623157e+308);
@@ -0,0 +1,314 @@
+extern crate num_traits;
+extern crate float_traits;
+
+use core::cmp::Ordering;
+use core::ops::{Add, Sub, Mul, Div, Rem, Neg};
+use self::num_traits::{Num, Zero, One, Bounded};
+use self::float_traits::{Abs, Sqrt, BinaryFloat, BoundedFloat, Infinite, Underflow, IEEE754Float};
+use traits::Fma;
+
+macro_rules! impl_lanewise_binop {
+    ($name:ident, $trait_:ident, $method:ident, $op:tt) => (
+        impl $trait_ for $name {
+            type Output = $name;
+            #[inline]
+            fn $method(self, rhs: $name) -> $name {
+                let mut res = self;
+                for (r, b) in res.0.iter_mut().zip(rhs.0.iter()) {
+                    *r $op *b;
+                }
+                res
+            }
+        }
+    )
+}
+
+macro_rules! impl_vector {
+    ($name:ident, $t:ident, $n:expr) => (
+        /// Vector of floating point numbers. Every arithmetic operation is applied lane-wise,
+        /// so the generic algorithms built only on arithmetic give lane-wise results on it:
+        ///
+        /// * `safetwosum_straight`, `safesplit_straight` and `safetwoproduct_straight`
+        /// * `safetwosum_fma`, `safetwoproduct_fma`, `safetwodiv_fma` and `safetwosqrt_fma`
+        /// * `twosum`, `fasttwosum`, `split` and `twoproduct`, under their preconditions
+        /// * `sum2` and `dot2` (and `***_with_err`), if every lane of every input is finite
+        ///   and no lane overflows
+        ///
+        /// Comparison is the lane-wise (product) order: `a <= b` iff every lane of `a` is
+        /// less than or equal to the corresponding lane of `b`, and `==` holds iff it holds
+        /// in every lane. The other functions (e.g. the `***_branch` EFTs, `safetwodiv`,
+        /// `Expansion`, the predicates and `checked_***`) branch on comparisons or on checks
+        /// for zero or infinity, and are not valid for vectors.
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub struct $name(pub [$t; $n]);
+
+        impl $name {
+            #[inline]
+            pub fn splat(x: $t) -> $name {
+                $name([x; $n])
+            }
+
+            /// Loads the lanes from the head of `a`. Panics if `a` is shorter than the vector.
+            #[inline]
+            pub fn from_slice(a: &[$t]) -> $name {
+                let mut res = [0.; $n];
+                res.copy_from_slice(&a[..$n]);
+                $name(res)
+            }
+
+            /// Stores the lanes to the head of `a`. Panics if `a` is shorter than the vector.
+            #[inline]
+            pub fn write_to_slice(self, a: &mut [$t]) {
+                a[..$n].copy_from_slice(&self.0);
+            }
+
+            #[inline]
+            fn map<F: Fn($t) -> $t>(self, f: F) -> $name {
+                let mut res = self;
+                for r in res.0.iter_mut() {
+                    *r = f(*r);
+                }
+                res
+            }
+        }
+
+        impl From<[$t; $n]> for $name {
+            #[inline]
+            fn from(a: [$t; $n]) -> $name {
+                $name(a)
+            }
+        }
+
+        impl From<$name> for [$t; $n] {
+            #[inline]
+            fn from(a: $name) -> [$t; $n] {
+                a.0
+            }
+        }
+
+        impl_lanewise_binop!($name, Add, add, +=);
+        impl_lanewise_binop!($name, Sub, sub, -=);
+        impl_lanewise_binop!($name, Mul, mul, *=);
+        impl_lanewise_binop!($name, Div, div, /=);
+        impl_lanewise_binop!($name, Rem, rem, %=);
+
+        impl Neg for $name {
+            type Output = $name;
+            #[inline]
+            fn neg(self) -> $name {
+                self.map(|x| -x)
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &$name) -> Option<Ordering> {
+                let mut res = Ordering::Equal;
+                for (a, b) in self.0.iter().zip(other.0.iter()) {
+                    match (res, a.partial_cmp(b)?) {
+                        (_, Ordering::Equal) => {}
+                        (Ordering::Equal, o) => res = o,
+                        (r, o) if r == o => {}
+                        _ => return None,
+                    }
+                }
+                Some(res)
+            }
+        }
+
+        impl Zero for $name {
+            #[inline]
+            fn zero() -> $name {
+                $name::splat(0.)
+            }
+            #[inline]
+            fn is_zero(&self) -> bool {
+                self.0.iter().all(|&x| x == 0.)
+            }
+        }
+
+        impl One for $name {
+            #[inline]
+            fn one() -> $name {
+                $name::splat(1.)
+            }
+        }
+
+        impl Num for $name {
+            type FromStrRadixErr = <$t as Num>::FromStrRadixErr;
+            #[inline]
+            fn from_str_radix(s: &str, radix: u32) -> Result<$name, Self::FromStrRadixErr> {
+                <$t as Num>::from_str_radix(s, radix).map($name::splat)
+            }
+        }
+
+        impl Bounded for $name {
+            #[inline]
+            fn min_value() -> $name {
+                $name::splat(<$t as Bounded>::min_value())
+            }
+            #[inline]
+            fn max_value() -> $name {
+                $name::splat(<$t as Bounded>::max_value())
+            }
+        }
+
+        impl Abs for $name {
+            type Output = $name;
+            #[inline]
+            fn abs(&self) -> $name {
+                self.map(|x| x.abs())
+            }
+            #[inline]
+            fn sign(&self) -> $name {
+                self.map(|x| x.signum())
+            }
+        }
+
+        impl Sqrt for $name {
+            type Output = $name;
+            #[inline]
+            fn sqrt(self) -> $name {
+                self.map(|x| x.sqrt())
+            }
+        }
+
+        impl BinaryFloat for $name {
+            type Expo = <$t as BinaryFloat>::Expo;
+            #[inline]
+            fn bits() -> Self::Expo {
+                <$t as BinaryFloat>::bits()
+            }
+            #[inline]
+            fn eps() -> $name {
+                $name::splat(<$t as BinaryFloat>::eps())
+            }
+            #[inline]
+            fn two_powi(p: Self::Expo) -> $name {
+                $name::splat(<$t as BinaryFloat>::two_powi(p))
+            }
+        }
+
+        impl BoundedFloat for $name {
+            #[inline]
+            fn max_exponent() -> Self::Exponent {
+                <$t as BoundedFloat>::max_exponent()
+            }
+            #[inline]
+            fn min_exponent() -> Self::Exponent {
+                <$t as BoundedFloat>::min_exponent()
+            }
+        }
+
+        impl Infinite for $name {
+            #[inline]
+            fn infinity() -> $name {
+                $name::splat(<$t as Infinite>::infinity())
+            }
+            #[inline]
+            fn neg_infinity() -> $name {
+                $name::splat(<$t as Infinite>::neg_infinity())
+            }
+            /// Returns `true` if any lane is infinite.
+            #[inline]
+            fn is_infinite(&self) -> bool {
+                self.0.iter().any(|x| x.is_infinite())
+            }
+        }
+
+        impl Underflow for $name {
+            #[inline]
+            fn min_positive() -> $name {
+                $name::splat(<$t as Underflow>::min_positive())
+            }
+            #[inline]
+            fn unit_underflow() -> $name {
+                $name::splat(<$t as Underflow>::unit_underflow())
+            }
+        }
+
+        impl IEEE754Float for $name {
+            #[inline]
+            fn nan() -> $name {
+                $name::splat(<$t as IEEE754Float>::nan())
+            }
+        }
+
+        impl Fma for $name {
+            #[inline]
+            fn fma(a: $name, b: $name, c: $name) -> $name {
+                let mut res = a;
+                for ((r, y), z) in res.0.iter_mut().zip(b.0.iter()).zip(c.0.iter()) {
+                    *r = r.mul_add(*y, *z);
+                }
+                res
+            }
+        }
+    )
+}
+
+impl_vector!(F64x4, f64, 4);
+impl_vector!(F32x8, f32, 8);
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use std::{f32, f64};
+    use self::rand::Rng;
+
+    use super::*;
+    use twosum::safetwosum_straight;
+    use twoprod::{safetwoproduct_straight, safetwoproduct_fma};
+    use split::safesplit_straight;
+
+    fn gen_f64(rng: &mut rand::ThreadRng) -> f64 {
+        rng.gen_range::<f64>(-1., 1.) * 2f64.powi(rng.gen_range(-1074, 1024))
+    }
+
+    #[test]
+    fn same_as_scalar() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100000 {
+            let x: Vec<f64> = (0..4).map(|_| gen_f64(&mut rng)).collect();
+            let y: Vec<f64> = (0..4).map(|_| gen_f64(&mut rng)).collect();
+            let (vx, vy) = (F64x4::from_slice(&x), F64x4::from_slice(&y));
+
+            let (s, e) = safetwosum_straight(vx, vy);
+            let (p, q) = safetwoproduct_straight(vx, vy);
+            let (pf, qf) = safetwoproduct_fma(vx, vy);
+            let (h, l, r) = safesplit_straight(vx);
+            for i in 0..4 {
+                let bits = |a: f64| a.to_bits();
+                let (ss, ee) = safetwosum_straight(x[i], y[i]);
+                assert_eq!((bits(s.0[i]), bits(e.0[i])), (bits(ss), bits(ee)));
+                let (pp, qq) = safetwoproduct_straight(x[i], y[i]);
+                assert_eq!((bits(p.0[i]), bits(q.0[i])), (bits(pp), bits(qq)));
+                let (pp, qq) = safetwoproduct_fma(x[i], y[i]);
+                assert_eq!((bits(pf.0[i]), bits(qf.0[i])), (bits(pp), bits(qq)));
+                let (hh, ll, rr) = safesplit_straight(x[i]);
+                assert_eq!((bits(h.0[i]), bits(l.0[i]), bits(r.0[i])),
+                           (bits(hh), bits(ll), bits(rr)));
+            }
+        }
+
+        let x = F32x8([1., -1., 3., f32::MAX, -f32::MAX, 1e-40, 0., 0.5]);
+        let (s, e) = safetwosum_straight(x, F32x8::splat(f32::EPSILON / 4.));
+        for i in 0..8 {
+            assert_eq!((s.0[i], e.0[i]), safetwosum_straight(x.0[i], f32::EPSILON / 4.));
+        }
+    }
+
+    #[test]
+    fn lanewise_order() {
+        let a = F64x4([1., 2., 3., 4.]);
+        let b = F64x4([1., 3., 3., 5.]);
+        assert!(a <= b && a < b);
+        assert_eq!(b.partial_cmp(&a), Some(Ordering::Greater));
+        assert_eq!(a.partial_cmp(&a), Some(Ordering::Equal));
+        assert_eq!(a.partial_cmp(&F64x4([0., 3., 3., 4.])), None);
+        assert_eq!(a.partial_cmp(&F64x4([1., 2., f64::NAN, 4.])), None);
+        let mut out = [0.; 5];
+        (-a).write_to_slice(&mut out);
+        assert_eq!(out, [-1., -2., -3., -4., 0.]);
+    }
+}